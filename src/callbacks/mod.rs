@@ -0,0 +1,71 @@
+//! ### Callbacks
+//! Provides the `Callback` trait, whose hooks are called by `NNetwork::fit` during the training,
+//! to log, checkpoint or stop it without changing the training loop.
+//!
+//! Callbacks are added to a network with `add_callback`. Each hook gets a read access to the network,
+//! and returns a `Control` telling whether the training should go on.
+
+use crate::history;
+use crate::NNetwork;
+
+/// Returned by the hooks of a `Callback`, to continue or stop the training.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    /// Stops the training once every callback got the current hook.\
+    /// The current batch (or epoch) is not trained if asked at its beginning.
+    Stop,
+}
+
+/// Hooks called during `NNetwork::fit`, all doing nothing by default.
+pub trait Callback {
+    /// Called once, before the first epoch.
+    fn on_train_begin(&mut self, _network: &NNetwork) -> Control {
+        Control::Continue
+    }
+    /// Called at the beginning of the epoch `epoch`.
+    fn on_epoch_begin(&mut self, _network: &NNetwork, _epoch: usize) -> Control {
+        Control::Continue
+    }
+    /// Called before the batch `batch` of the epoch `epoch` is trained.
+    fn on_batch_begin(&mut self, _network: &NNetwork, _epoch: usize, _batch: usize) -> Control {
+        Control::Continue
+    }
+    /// Called once the batch `batch` of the epoch `epoch` is trained, with its `loss`.
+    fn on_batch_end(
+        &mut self,
+        _network: &NNetwork,
+        _epoch: usize,
+        _batch: usize,
+        _loss: f64,
+    ) -> Control {
+        Control::Continue
+    }
+    /// Called at the end of each epoch, with what was recorded in the `History` for it.
+    fn on_epoch_end(&mut self, _network: &NNetwork, _logs: &history::Epoch) -> Control {
+        Control::Continue
+    }
+    /// Called once, when the training ends (even if it was stopped).
+    fn on_train_end(&mut self, _network: &NNetwork) {}
+    /// Returns a boxed copy of the callback, used to clone the network.
+    fn box_clone(&self) -> Box<dyn Callback>;
+}
+
+impl Clone for Box<dyn Callback> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Calls `hook` on every callback, and returns `true` if one of them asked to stop.
+pub(crate) fn notify<F>(callbacks: &mut [Box<dyn Callback>], mut hook: F) -> bool
+where
+    F: FnMut(&mut dyn Callback) -> Control,
+{
+    // Every callback gets the hook, even once one asked to stop
+    let mut stop = false;
+    for callback in callbacks.iter_mut() {
+        stop |= hook(callback.as_mut()) == Control::Stop;
+    }
+    stop
+}
@@ -0,0 +1,220 @@
+//! ### Early stopping
+//! Provides `EarlyStopping`, that stops `NNetwork::fit` once a monitored quantity stops improving.\
+//! Set it with `set_early_stopping` : the number of epochs then becomes an upper bound.
+
+use crate::history;
+use crate::layers::Layer;
+use crate::{Array2, BatchNorm, Metric};
+
+/// Quantity watched by `EarlyStopping`, read at the end of each epoch.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Monitor {
+    /// Mean loss over the training batches.
+    TrainLoss,
+    /// Loss over the testing datas.
+    TestLoss,
+    /// Given metric over the testing datas, evaluated at the end of each epoch.
+    Metric(Metric),
+}
+impl Monitor {
+    /// Returns the direction in which the monitored quantity improves.
+    pub fn mode(&self) -> Mode {
+        match self {
+            Monitor::Metric(Metric::Accuracy)
+            | Monitor::Metric(Metric::Precision)
+            | Monitor::Metric(Metric::Recall)
+            | Monitor::Metric(Metric::F1)
+            | Monitor::Metric(Metric::R2) => Mode::Max,
+            _ => Mode::Min,
+        }
+    }
+
+    /// Returns the value of the monitored quantity in the `logs` of an epoch, if it was recorded.
+    pub fn value(&self, logs: &history::Epoch) -> Option<f64> {
+        match self {
+            Monitor::TrainLoss => Some(logs.train_loss),
+            Monitor::TestLoss => logs.test_loss,
+            Monitor::Metric(metric) => logs.metrics.get(metric.name()).cloned(),
+        }
+    }
+}
+
+/// Whether the monitored quantity improves when it decreases or increases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Min,
+    Max,
+}
+
+/// Tracks the best value of a monitored quantity, and the number of epochs since it last improved.\
+/// Shared by `EarlyStopping` and `schedules::ReduceOnPlateau`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Plateau {
+    pub(crate) best: Option<f64>,
+    /// Epochs without improvement.
+    pub(crate) wait: usize,
+}
+
+impl Plateau {
+    /// Reads the value of `monitor` in the `logs` of an epoch, and returns whether it improved
+    /// on the best one by more than `min_delta` in given `mode`.\
+    /// Returns `None`, ignoring the epoch, if the value was not recorded or is NaN.
+    pub(crate) fn update(
+        &mut self,
+        monitor: &Monitor,
+        mode: Mode,
+        min_delta: f64,
+        logs: &history::Epoch,
+    ) -> Option<bool> {
+        let value = match monitor.value(logs) {
+            Some(value) if !value.is_nan() => value,
+            _ => return None,
+        };
+        let improved = match (self.best, mode) {
+            (None, _) => true,
+            (Some(best), Mode::Min) => value < best - min_delta,
+            (Some(best), Mode::Max) => value > best + min_delta,
+        };
+
+        if improved {
+            self.best = Some(value);
+            self.wait = 0;
+        } else {
+            self.wait += 1;
+        }
+        Some(improved)
+    }
+}
+
+/// Stops the training when the monitored quantity did not improve
+/// by more than `min_delta` for `patience` epochs.
+#[derive(Clone, Debug)]
+pub struct EarlyStopping {
+    pub monitor: Monitor,
+    pub patience: usize,
+    pub min_delta: f64,
+    pub mode: Mode,
+    /// If set, the parameters of the best epoch (and the running statistics of the `BatchNorm` layers)
+    /// are restored at the end of the training.
+    pub restore_best_weights: bool,
+    plateau: Plateau,
+    best_epoch: Option<usize>,
+    best_weights: Option<Vec<Snapshot>>,
+}
+
+/// Parameters of a layer at the best epoch, with the running statistics of a `BatchNorm`.
+#[derive(Clone, Debug)]
+struct Snapshot {
+    parameters: Vec<Array2<f64>>,
+    running_stats: Option<(Array2<f64>, Array2<f64>)>,
+}
+
+impl Snapshot {
+    fn new(layer: &dyn Layer) -> Self {
+        Self {
+            parameters: layer.parameters().into_iter().cloned().collect(),
+            running_stats: layer
+                .as_any()
+                .downcast_ref::<BatchNorm>()
+                .map(|b| (b.running_mean.clone(), b.running_var.clone())),
+        }
+    }
+
+    fn restore(self, layer: &mut dyn Layer) {
+        for (param, saved) in layer.parameters_mut().into_iter().zip(self.parameters) {
+            *param = saved;
+        }
+        if let Some((mean, var)) = self.running_stats {
+            if let Some(batch_norm) = layer.as_any_mut().downcast_mut::<BatchNorm>() {
+                batch_norm.running_mean = mean;
+                batch_norm.running_var = var;
+            }
+        }
+    }
+}
+
+impl EarlyStopping {
+    /// Returns a new `EarlyStopping` watching `monitor`, stopping after `patience` epochs without improvement.\
+    /// The mode is chosen from the monitored quantity (`Max` for accuracy, precision, recall, F1 and R², `Min` else),
+    /// `min_delta` is `0` and the best weights are not restored.
+    pub fn new(monitor: Monitor, patience: usize) -> Self {
+        Self {
+            mode: monitor.mode(),
+            monitor,
+            patience,
+            min_delta: 0.,
+            restore_best_weights: false,
+            plateau: Default::default(),
+            best_epoch: None,
+            best_weights: None,
+        }
+    }
+
+    /// Only count as improvements changes greater than `min_delta`.
+    pub fn with_min_delta(mut self, min_delta: f64) -> Self {
+        self.min_delta = min_delta.abs();
+        self
+    }
+
+    /// Set whether the monitored quantity should be minimized or maximized.
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Restore the parameters of the best epoch at the end of the training.
+    pub fn with_restore_best_weights(mut self) -> Self {
+        self.restore_best_weights = true;
+        self
+    }
+
+    /// Returns the best value of the monitored quantity, in the last training.
+    pub fn best(&self) -> Option<f64> {
+        self.plateau.best
+    }
+
+    /// Returns the epoch where the monitored quantity was the best, in the last training.
+    pub fn best_epoch(&self) -> Option<usize> {
+        self.best_epoch
+    }
+
+    /// Forgets the previous training (called at the beginning of `fit`).
+    pub(crate) fn reset(&mut self) {
+        self.plateau = Default::default();
+        self.best_epoch = None;
+        self.best_weights = None;
+    }
+
+    /// Updates the state from the `logs` of an epoch trained to `layers`,
+    /// and returns `true` if the training should stop.
+    pub(crate) fn update(&mut self, logs: &history::Epoch, layers: &[Box<dyn Layer>]) -> bool {
+        let improved = self
+            .plateau
+            .update(&self.monitor, self.mode, self.min_delta, logs);
+        match improved {
+            Some(true) => {
+                self.best_epoch = Some(logs.epoch);
+                if self.restore_best_weights {
+                    self.best_weights = Some(layers.iter().map(|l| Snapshot::new(&**l)).collect());
+                }
+                false
+            }
+            Some(false) => self.plateau.wait >= self.patience,
+            None => false,
+        }
+    }
+
+    /// Restores the parameters of the best epoch in `layers`, if they were kept,
+    /// and returns `true` if they were.
+    pub(crate) fn restore_best(&mut self, layers: &mut [Box<dyn Layer>]) -> bool {
+        match self.best_weights.take() {
+            Some(snapshots) => {
+                for (layer, snapshot) in layers.iter_mut().zip(snapshots) {
+                    snapshot.restore(&mut **layer);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
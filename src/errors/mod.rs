@@ -0,0 +1,165 @@
+//! ### Errors
+//! Provides `SpitzError`, returned by every fallible call of the library instead of panicking.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Errors that can be returned by the library.
+#[derive(Debug)]
+pub enum SpitzError {
+    /// `x` and `y` do not have the same number of samples.
+    ShapeMismatch { x: usize, y: usize },
+    /// The test ratio is not strictly between `0` and `1`.
+    InvalidRatio(f64),
+    /// The input layer size was not set with `input_layer`, and cannot be inferred from training datas.
+    MissingInputLayer,
+    /// A layer was defined without any neuron.
+    InvalidLayerSize,
+    /// The number of epochs is `0`.
+    InvalidEpochs,
+    /// The number of datas per batch is `0`, or every batch would be dropped
+    /// (more datas per batch than training datas, with `set_drop_last`).
+    InvalidBatchSize,
+    /// There is no layer at `index` : the network only has `layers` layers.
+    InvalidLayerIndex { index: usize, layers: usize },
+    /// The layer at `index` has no weights to set.
+    LayerWithoutWeights(usize),
+    /// A layer cannot be saved, as the file format does not know it.
+    UnsupportedLayer(String),
+    /// A layer cannot take samples of the shape given by the previous one.
+    IncompatibleShape {
+        layer: &'static str,
+        shape: Vec<usize>,
+    },
+    /// A dropout rate is not in `[0, 1)`.
+    InvalidDropoutRate(f64),
+    /// The parameters of a weights initializer are invalid.
+    InvalidInitializer(String),
+    /// The network has no layer.
+    EmptyArchitecture,
+    /// The network must be initialized with `init` first.
+    UninitializedNetwork,
+    /// No training datas were imported.
+    MissingTrainingDatas,
+    /// No testing datas were imported.
+    MissingTestDatas,
+    /// Some datas do not have the width of the layer they are given to.
+    IncompatibleDataWidth {
+        /// What the datas are used for, e.g. `"training inputs"`.
+        datas: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    /// A file was written with another version of the file format.
+    IncompatibleVersion { expected: u32, found: u32 },
+    /// A file is not a valid network file.
+    InvalidFile(String),
+    /// An input/output error occured while reading or writing a file.
+    Io(io::Error),
+}
+
+impl fmt::Display for SpitzError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpitzError::ShapeMismatch { x, y } => {
+                write!(f, "x and y must be aligned ({} != {})", x, y)
+            }
+            SpitzError::InvalidRatio(ratio) => write!(
+                f,
+                "test ratio must be between 0.0 and 1.0 (ratio = {})",
+                ratio
+            ),
+            SpitzError::MissingInputLayer => write!(
+                f,
+                "cannot determine input layer size. Please set it with _network_.input_layer(size) or import training datas"
+            ),
+            SpitzError::InvalidLayerSize => write!(f, "a layer must have at least one neuron"),
+            SpitzError::InvalidEpochs => {
+                write!(f, "the network must be trained for at least one epoch")
+            }
+            SpitzError::InvalidBatchSize => write!(
+                f,
+                "a batch must have at least one data, and no more than the training datas if incomplete batches are dropped"
+            ),
+            SpitzError::InvalidLayerIndex { index, layers } => write!(
+                f,
+                "no layer at index {}, the network has {} layers",
+                index, layers
+            ),
+            SpitzError::LayerWithoutWeights(index) => {
+                write!(f, "the layer at index {} has no weights", index)
+            }
+            SpitzError::UnsupportedLayer(name) => {
+                write!(f, "the layer `{}` cannot be saved", name)
+            }
+            SpitzError::IncompatibleShape { layer, shape } => write!(
+                f,
+                "the layer `{}` cannot take samples of shape {:?}",
+                layer, shape
+            ),
+            SpitzError::InvalidDropoutRate(rate) => write!(
+                f,
+                "dropout rate must be between 0.0 (included) and 1.0 (excluded) (rate = {})",
+                rate
+            ),
+            SpitzError::EmptyArchitecture => write!(
+                f,
+                "the network has no layer. Please add some with _network_.add_layer(size, activation)"
+            ),
+            SpitzError::UninitializedNetwork => write!(
+                f,
+                "the network is not initialized. Please call _network_.init() first"
+            ),
+            SpitzError::MissingTrainingDatas => write!(
+                f,
+                "no training datas. Please import some with _network_.import_train_datas(x, y)"
+            ),
+            SpitzError::MissingTestDatas => write!(
+                f,
+                "no testing datas. Please import some with _network_.import_test_datas(x, y)"
+            ),
+            SpitzError::IncompatibleDataWidth {
+                datas,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} have {} columns, but the {} layer has {} neurons",
+                datas,
+                found,
+                if datas.ends_with("inputs") {
+                    "input"
+                } else {
+                    "output"
+                },
+                expected
+            ),
+            SpitzError::IncompatibleVersion { expected, found } => write!(
+                f,
+                "incompatible file format version {} (this version of spitz reads version {})",
+                found, expected
+            ),
+            SpitzError::InvalidInitializer(message) => {
+                write!(f, "invalid weights initializer : {}", message)
+            }
+            SpitzError::InvalidFile(message) => write!(f, "invalid network file : {}", message),
+            SpitzError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for SpitzError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SpitzError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SpitzError {
+    fn from(e: io::Error) -> Self {
+        SpitzError::Io(e)
+    }
+}
@@ -0,0 +1,154 @@
+//! ### History
+//! Provides `History`, the record of a training made by `NNetwork::fit`,
+//! that can be exported to CSV or JSON to plot the learning curves.
+
+use crate::SpitzError;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+// * Epoch struct
+/// What happened during one epoch of training.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Epoch {
+    /// Index of the epoch, starting at `0`.
+    pub epoch: usize,
+    /// Mean loss over the training batches.
+    pub train_loss: f64,
+    /// Loss over the testing datas, if some were imported.
+    pub test_loss: Option<f64>,
+    /// Value of each metric computed at the end of the epoch, by name.
+    pub metrics: BTreeMap<String, f64>,
+    /// Learning rate used during the epoch.
+    pub learning_rate: f64,
+    /// Wall time spent on the epoch.
+    pub duration: Duration,
+}
+
+// * History struct
+/// Record of the last training of a network, one `Epoch` per trained epoch.\
+/// Get it after `fit` with `get_history`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    pub epochs: Vec<Epoch>,
+}
+
+impl History {
+    /// Returns the training loss of each epoch.
+    pub fn train_loss(&self) -> Vec<f64> {
+        self.epochs.iter().map(|e| e.train_loss).collect()
+    }
+
+    /// Returns the testing loss of each epoch, if any.
+    pub fn test_loss(&self) -> Vec<Option<f64>> {
+        self.epochs.iter().map(|e| e.test_loss).collect()
+    }
+
+    /// Returns the value of the metric `name` at each epoch, if it was computed.
+    pub fn metric(&self, name: &str) -> Vec<Option<f64>> {
+        self.epochs
+            .iter()
+            .map(|e| e.metrics.get(name).cloned())
+            .collect()
+    }
+
+    /// Returns the names of every metric recorded, sorted.
+    fn metric_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .epochs
+            .iter()
+            .flat_map(|e| e.metrics.keys().map(String::as_str))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Returns the history as CSV, one line per epoch.\
+    /// Columns are `epoch,train_loss,test_loss,learning_rate,duration`, then one column per metric ;
+    /// missing values are left empty and durations are in seconds.
+    pub fn to_csv(&self) -> String {
+        let names = self.metric_names();
+        let mut csv = String::from("epoch,train_loss,test_loss,learning_rate,duration");
+        for name in &names {
+            csv.push(',');
+            csv.push_str(name);
+        }
+        csv.push('\n');
+
+        for e in &self.epochs {
+            let _ = write!(
+                csv,
+                "{},{},{},{},{}",
+                e.epoch,
+                e.train_loss,
+                e.test_loss.map(|l| l.to_string()).unwrap_or_default(),
+                e.learning_rate,
+                e.duration.as_secs_f64()
+            );
+            for name in &names {
+                csv.push(',');
+                if let Some(value) = e.metrics.get(*name) {
+                    csv.push_str(&value.to_string());
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Returns the history as a JSON object, of the form
+    /// `{"epochs": [{"epoch": 0, "train_loss": .., "test_loss": .., "learning_rate": .., "duration": .., "metrics": {..}}]}`.\
+    /// Missing and non-finite values are written as `null`, durations are in seconds.
+    pub fn to_json(&self) -> String {
+        let epochs: Vec<String> = self
+            .epochs
+            .iter()
+            .map(|e| {
+                let metrics: Vec<String> = e
+                    .metrics
+                    .iter()
+                    .map(|(name, &value)| format!("{:?}: {}", name, json_number(Some(value))))
+                    .collect();
+                format!(
+                    "{{\"epoch\": {}, \"train_loss\": {}, \"test_loss\": {}, \"learning_rate\": {}, \"duration\": {}, \"metrics\": {{{}}}}}",
+                    e.epoch,
+                    json_number(Some(e.train_loss)),
+                    json_number(e.test_loss),
+                    json_number(Some(e.learning_rate)),
+                    json_number(Some(e.duration.as_secs_f64())),
+                    metrics.join(", ")
+                )
+            })
+            .collect();
+        format!("{{\"epochs\": [{}]}}", epochs.join(", "))
+    }
+
+    /// Writes the history as CSV (see `to_csv`) to the file at `path`.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::Io` if the file cannot be written.
+    pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> Result<(), SpitzError> {
+        fs::write(path, self.to_csv())?;
+        Ok(())
+    }
+
+    /// Writes the history as JSON (see `to_json`) to the file at `path`.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::Io` if the file cannot be written.
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), SpitzError> {
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+}
+
+/// Formats `value` as a JSON number, or `null` if it is missing or not finite.
+fn json_number(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v.is_finite() => v.to_string(),
+        _ => String::from("null"),
+    }
+}
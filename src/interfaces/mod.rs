@@ -4,8 +4,14 @@
 //! - `PublicCalls`, to interface with the user : import datas, set parameters ;
 //! - `PrivateCalls`, provides private functions to init the network.
 
+use crate::layers::shape_size;
+use crate::optimizers::Optimizer;
 use crate::types::*;
-use crate::{array, log::*, maths, Array, Array2, Axis, RandomExt, Uniform};
+use crate::{log::*, maths, Array2, Axis, SeedableRng, StdRng};
+use crate::{
+    BatchNorm, Callback, Constraint, Dropout, EarlyStopping, History, Layer, LearningRateSchedule,
+    Monitor, Regularizer, SpitzError,
+};
 
 /// Public callers (get and set methods).
 pub trait PublicCalls {
@@ -19,78 +25,211 @@ pub trait PublicCalls {
     /// The minimum number of datas selected for training will be `1`.
     ///
     /// ### Returns
-    /// Returns a tuple containing the number of testing and training selected datas.
+    /// Returns the network, whose testing datas are the last rows of `x` and `y`, and training datas the first ones.
     ///
-    /// ### Panics
-    /// Panics if `test_ratio` is not between `0` and `1`.\
-    /// Panics if `x` and `y` are not the same shape.
-    fn import_datas(&mut self, x: &Array2<f64>, y: &Array2<f64>, test_ratio: f64) -> &mut Self;
+    /// ### Errors
+    /// Returns `SpitzError::InvalidRatio` if `test_ratio` is not between `0` and `1`.\
+    /// Returns `SpitzError::ShapeMismatch` if `x` and `y` do not have the same number of rows.\
+    /// Returns `SpitzError::MissingTrainingDatas` if there are less than `2` rows to split.
+    fn import_datas(
+        &mut self,
+        x: &Array2<f64>,
+        y: &Array2<f64>,
+        test_ratio: f64,
+    ) -> Result<&mut Self, SpitzError>;
     // TODO documentation for `import_train_datas` and `import_test_datas`
     /// Import only training datas
-    fn import_train_datas(&mut self, x: &Array2<f64>, y: &Array2<f64>) -> &mut Self;
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::ShapeMismatch` if `x` and `y` do not have the same number of rows.
+    fn import_train_datas(
+        &mut self,
+        x: &Array2<f64>,
+        y: &Array2<f64>,
+    ) -> Result<&mut Self, SpitzError>;
     /// Import only testing datas
-    fn import_test_datas(&mut self, x: &Array2<f64>, y: &Array2<f64>) -> &mut Self;
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::ShapeMismatch` if `x` and `y` do not have the same number of rows.
+    fn import_test_datas(
+        &mut self,
+        x: &Array2<f64>,
+        y: &Array2<f64>,
+    ) -> Result<&mut Self, SpitzError>;
     /// Set learning rate.
     fn set_learning_rate(&mut self, rate: f64) -> &mut Self;
     /// Set epochs number.
-    fn set_epochs(&mut self, epochs: i32) -> &mut Self;
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::InvalidEpochs` if `epochs` is `0`.
+    fn set_epochs(&mut self, epochs: usize) -> Result<&mut Self, SpitzError>;
     /// Set the number of datas per batch.
-    fn set_batches(&mut self, batches: i32) -> &mut Self;
-    /// Add a layer to the architecture.
-    fn add_layer(&mut self, neurons: usize, activation: maths::Activation) -> &mut Self;
-    /// Define input layer size of the architecture.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::InvalidBatchSize` if `batches` is `0`.
+    fn set_batches(&mut self, batches: usize) -> Result<&mut Self, SpitzError>;
+    /// Set whether the training samples are shuffled at the beginning of each epoch (`true` by default).
+    fn set_shuffle(&mut self, shuffle: bool) -> &mut Self;
+    /// Set whether the last batch of each epoch is dropped when it has less than `batches` datas
+    /// (`false` by default).
+    fn set_drop_last(&mut self, drop_last: bool) -> &mut Self;
+    /// Set the metrics computed by `evaluate`, e.g. `vec![Metric::Accuracy, Metric::F1]`.
+    fn set_metrics(&mut self, metrics: Vec<maths::Metric>) -> &mut Self;
+    /// Set whether the network is evaluated on the testing datas at the end of each epoch
+    /// (`false` by default) : its metrics are then recorded in the `History`.
+    fn set_evaluate_each_epoch(&mut self, evaluate: bool) -> &mut Self;
+    /// Set the penalty added to the training loss for the weights of every layer,
+    /// excepted those given one with `set_layer_regularizer`.
+    fn set_regularizer(&mut self, regularizer: Regularizer) -> &mut Self;
+    /// Set the constraint applied to the weights of every layer after each update,
+    /// excepted those given one with `set_layer_constraint`.
+    fn set_constraint(&mut self, constraint: Constraint) -> &mut Self;
+    /// Set the penalty added to the training loss for the weights of the layer at `index`
+    /// (`0` being the first layer added after the input layer, dropouts and normalizations included).
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::InvalidLayerIndex` if there is no such layer
+    /// (an output layer set with `output_layer` only exists once the network is initialized).\
+    /// Returns `SpitzError::LayerWithoutWeights` if the layer has no weights.
+    fn set_layer_regularizer(
+        &mut self,
+        index: usize,
+        regularizer: Regularizer,
+    ) -> Result<&mut Self, SpitzError>;
+    /// Set the constraint applied to the weights of the layer at `index` after each update.
+    ///
+    /// ### Errors
+    /// Returns an error in the same cases as `set_layer_regularizer`.
+    fn set_layer_constraint(
+        &mut self,
+        index: usize,
+        constraint: Constraint,
+    ) -> Result<&mut Self, SpitzError>;
+    /// Set the early stopping, that ends the training once the quantity it monitors stops improving :
+    /// the number of epochs is then an upper bound.\
+    /// A monitored metric is added to the network metrics if needed.
+    fn set_early_stopping(&mut self, early_stopping: EarlyStopping) -> &mut Self;
+    /// Set the loss function minimized during training.
+    fn set_loss(&mut self, loss: maths::Loss) -> &mut Self;
+    /// Set the optimizer used to update parameters during training.\
+    /// Its hyperparameters are set when building it, e.g. `optimizers::Adam::new(0.9, 0.999, 1e-8)`.
+    fn set_optimizer<O: Optimizer + 'static>(&mut self, optimizer: O) -> &mut Self;
+    /// Set the schedule choosing the learning rate of each epoch from the network learning rate.\
+    /// See `schedules`, e.g. `schedules::StepDecay::new(10, 0.5)`.\
+    /// A metric monitored by the schedule is added to the network metrics if needed.
+    fn set_learning_rate_schedule<S: LearningRateSchedule + 'static>(
+        &mut self,
+        schedule: S,
+    ) -> &mut Self;
+    /// Add a callback, whose hooks are called during training (see `Callback`).
+    fn add_callback<C: Callback + 'static>(&mut self, callback: C) -> &mut Self;
+    /// Set the seed of the random generator used by every stochastic part of the network
+    /// (weights initialization, datas shuffling, dropout...).\
+    /// Two networks with the same configuration and seed give exactly the same results.
+    fn set_seed(&mut self, seed: u64) -> &mut Self;
+    /// Add a `Dense` layer to the architecture.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::InvalidLayerSize` if `neurons` is `0`.
+    fn add_layer(
+        &mut self,
+        neurons: usize,
+        activation: maths::Activation,
+    ) -> Result<&mut Self, SpitzError>;
+    /// Add a `Dense` layer to the architecture, whose weights are drawn with given `initializer`
+    /// instead of the global or default one.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::InvalidLayerSize` if `neurons` is `0`.
+    fn add_layer_with_initializer(
+        &mut self,
+        neurons: usize,
+        activation: maths::Activation,
+        initializer: maths::Initializer,
+    ) -> Result<&mut Self, SpitzError>;
+    /// Set the initializer used to draw the weights of every layer,
+    /// excepted those added with `add_layer_with_initializer`.\
+    /// By default, each layer uses the initializer suiting its activation (see `Initializer::for_activation`).
+    fn set_initializer(&mut self, initializer: maths::Initializer) -> &mut Self;
+    /// Add any layer to the architecture, e.g. a custom one implementing `Layer`.
+    fn add<L: Layer + 'static>(&mut self, layer: L) -> &mut Self;
+    /// Add a layer applying `dropout` to the outputs of the last layer added, during training only.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::InvalidDropoutRate` if the rate of `dropout` is not in `[0, 1)`.\
+    /// Returns `SpitzError::EmptyArchitecture` if no layer was added.
+    fn add_dropout(&mut self, dropout: Dropout) -> Result<&mut Self, SpitzError>;
+    /// Add `batch_norm`, normalizing the outputs of the last layer added.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::EmptyArchitecture` if no layer was added.
+    fn add_batch_norm(&mut self, batch_norm: BatchNorm) -> Result<&mut Self, SpitzError>;
+    /// Define input layer size of the architecture.\
+    /// If it is not set, `init` uses the width of the training inputs.
     fn input_layer(&mut self, neurons: usize) -> &mut Self;
+    /// Define the shape of the input samples, e.g. `[channels, height, width]` for images
+    /// given to a `Conv2D` layer.\
+    /// The training inputs are still one flattened sample per row, of the size of the shape.
+    fn input_shape(&mut self, shape: &[usize]) -> &mut Self;
+    /// Add an output layer to the architecture, whose size is the width of the training outputs.\
+    /// It stays the last layer, even if other layers are added after it.
+    fn output_layer(&mut self, activation: maths::Activation) -> &mut Self;
 
     /// Init each part of the network.
-    fn init(&mut self) -> &mut Self;
+    ///
+    /// The input layer size is inferred from the training inputs if it was not set.\
+    /// The weights are drawn again at each call, excepted the first one after `NNetwork::load`,
+    /// which keeps the loaded parameters.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::MissingInputLayer` if the input layer size is not set and there is no training datas.\
+    /// Returns `SpitzError::MissingTrainingDatas` if an `output_layer` must be sized without training datas.\
+    /// Returns `SpitzError::EmptyArchitecture` if no layer was added.\
+    /// Returns `SpitzError::IncompatibleDataWidth` if imported datas do not fit the input or output layer.\
+    /// Returns `SpitzError::InvalidInitializer` if an initializer has invalid parameters.
+    fn init(&mut self) -> Result<&mut Self, SpitzError>;
 
     /// Returns architecture of given network.
     fn get_architecture(&self) -> Architecture;
-    /// Returns weights of given network.
+    /// Returns weights of given network, one matrix per layer having some.
     fn get_weights(&self) -> Weights;
+    /// Returns biases of given network, one `(1, size)` row per layer having some.
+    fn get_biases(&self) -> Biases;
+    /// Returns the history of the last training, see `History`.
+    fn get_history(&self) -> History;
 }
 
 impl PublicCalls for crate::NNetwork {
-    fn import_datas(&mut self, x: &Array2<f64>, y: &Array2<f64>, test_ratio: f64) -> &mut Self {
-        // Panics test
+    fn import_datas(
+        &mut self,
+        x: &Array2<f64>,
+        y: &Array2<f64>,
+        test_ratio: f64,
+    ) -> Result<&mut Self, SpitzError> {
+        // Errors test
         if (test_ratio <= 0.) || (test_ratio >= 1.) {
-            panic!(
-                "test ratio must be between 0.0 and 1.0 (ratio = {})",
-                test_ratio
-            );
-        };
-        if x.shape()[0] != y.shape()[0] {
-            panic!(
-                "x and y must be aligned ({} != {})",
-                x.shape()[0],
-                y.shape()[0]
-            )
+            return Err(SpitzError::InvalidRatio(test_ratio));
         };
+        check_aligned(x, y)?;
+        // Both sets need at least one data
+        let rows = x.nrows();
+        if rows < 2 {
+            return Err(SpitzError::MissingTrainingDatas);
+        }
         trace!("Test ratio = {:?}", test_ratio);
 
-        // Extract datas and set them
-
-        // Get the number of test datas
-        let mut test_number = (x.shape()[0] as f64 * test_ratio).round() as i32;
-        if test_number == 0 {
-            test_number = 1;
-        } else if test_number == x.shape()[0] as i32 {
-            test_number -= 1;
-        };
-        let mut train_number =
-            (x.shape()[0] as f64 - x.shape()[0] as f64 * test_ratio).round() as i32;
-        if train_number == x.shape()[0] as i32 {
-            train_number -= 1;
-        } else if train_number == 0 {
-            train_number = 1;
-        }
+        // Get the number of test datas, the others being used for training
+        let test_number = ((rows as f64 * test_ratio).round() as usize)
+            .max(1)
+            .min(rows - 1);
+        let train_number = rows - test_number;
 
         // Extract and set datas
         self.datas_raw.test_x = x
-            .slice_axis(Axis(0), ndarray::Slice::from(-test_number..))
+            .slice_axis(Axis(0), ndarray::Slice::from(train_number..))
             .to_owned();
         self.datas_raw.test_y = y
-            .slice_axis(Axis(0), ndarray::Slice::from(-test_number..))
+            .slice_axis(Axis(0), ndarray::Slice::from(train_number..))
             .to_owned();
         self.datas_raw.train_x = x
             .slice_axis(Axis(0), ndarray::Slice::from(0..train_number))
@@ -99,114 +238,342 @@ impl PublicCalls for crate::NNetwork {
             .slice_axis(Axis(0), ndarray::Slice::from(0..train_number))
             .to_owned();
 
-        self
+        Ok(self)
     }
-    fn import_train_datas(&mut self, x: &Array2<f64>, y: &Array2<f64>) -> &mut Self {
-        // Panics test
-        if x.shape()[0] != y.shape()[0] {
-            panic!(
-                "x and y must be aligned ({} != {})",
-                x.shape()[0],
-                y.shape()[0]
-            )
-        };
+    fn import_train_datas(
+        &mut self,
+        x: &Array2<f64>,
+        y: &Array2<f64>,
+    ) -> Result<&mut Self, SpitzError> {
+        // Errors test
+        check_aligned(x, y)?;
 
         // Extract datas and set them
         self.datas_raw.train_x = x.to_owned();
         self.datas_raw.train_y = y.to_owned();
 
-        self
+        Ok(self)
     }
-    fn import_test_datas(&mut self, x: &Array2<f64>, y: &Array2<f64>) -> &mut Self {
-        // Panics test
-        if x.shape()[0] != y.shape()[0] {
-            panic!(
-                "x and y must be aligned ({} != {})",
-                x.shape()[0],
-                y.shape()[0]
-            )
-        };
+    fn import_test_datas(
+        &mut self,
+        x: &Array2<f64>,
+        y: &Array2<f64>,
+    ) -> Result<&mut Self, SpitzError> {
+        // Errors test
+        check_aligned(x, y)?;
 
         // Extract datas and set them
         self.datas_raw.test_x = x.to_owned();
         self.datas_raw.test_y = y.to_owned();
 
-        self
+        Ok(self)
     }
     fn set_learning_rate(&mut self, rate: f64) -> &mut Self {
         self.learning_rate = rate;
         trace!("Learning rate set : {:?}", rate);
         self
     }
-    fn set_epochs(&mut self, epochs: i32) -> &mut Self {
-        self.epochs = epochs as usize;
+    fn set_epochs(&mut self, epochs: usize) -> Result<&mut Self, SpitzError> {
+        if epochs == 0 {
+            return Err(SpitzError::InvalidEpochs);
+        }
+        self.epochs = epochs;
         trace!("Epochs number set : {:?}", epochs);
-        self
+        Ok(self)
     }
-    fn set_batches(&mut self, batches: i32) -> &mut Self {
-        self.batches = batches as usize;
+    fn set_batches(&mut self, batches: usize) -> Result<&mut Self, SpitzError> {
+        if batches == 0 {
+            return Err(SpitzError::InvalidBatchSize);
+        }
+        self.batches = batches;
         trace!("Batches number set : {:?}", batches);
+        Ok(self)
+    }
+    fn set_shuffle(&mut self, shuffle: bool) -> &mut Self {
+        self.shuffle = shuffle;
+        trace!("Shuffle set : {:?}", shuffle);
         self
     }
-    fn add_layer(&mut self, neurons: usize, activation: maths::Activation) -> &mut Self {
-        match self.architecture.add_layer(neurons, activation.clone()) {
-            Ok(()) => {
-                trace!(
-                    "Adding layer with {:?} neurons and activation {:?}",
-                    neurons,
-                    activation
-                );
-                self
+    fn set_drop_last(&mut self, drop_last: bool) -> &mut Self {
+        self.drop_last = drop_last;
+        trace!("Drop last set : {:?}", drop_last);
+        self
+    }
+    fn set_metrics(&mut self, metrics: Vec<maths::Metric>) -> &mut Self {
+        trace!("Metrics set : {:?}", metrics);
+        self.metrics = metrics;
+        self
+    }
+    fn set_evaluate_each_epoch(&mut self, evaluate: bool) -> &mut Self {
+        self.evaluate_each_epoch = evaluate;
+        trace!("Evaluate each epoch set : {:?}", evaluate);
+        self
+    }
+    fn set_regularizer(&mut self, regularizer: Regularizer) -> &mut Self {
+        trace!("Regularizer set : {:?}", regularizer);
+        self.regularizer = Some(regularizer);
+        self
+    }
+    fn set_constraint(&mut self, constraint: Constraint) -> &mut Self {
+        trace!("Constraint set : {:?}", constraint);
+        self.constraint = Some(constraint);
+        self
+    }
+    fn set_layer_regularizer(
+        &mut self,
+        index: usize,
+        regularizer: Regularizer,
+    ) -> Result<&mut Self, SpitzError> {
+        trace!("Regularizer of layer {} set : {:?}", index, regularizer);
+        self.architecture
+            .layer_mut(index)?
+            .settings_mut()
+            .ok_or(SpitzError::LayerWithoutWeights(index))?
+            .regularizer = Some(regularizer);
+        Ok(self)
+    }
+    fn set_layer_constraint(
+        &mut self,
+        index: usize,
+        constraint: Constraint,
+    ) -> Result<&mut Self, SpitzError> {
+        trace!("Constraint of layer {} set : {:?}", index, constraint);
+        self.architecture
+            .layer_mut(index)?
+            .settings_mut()
+            .ok_or(SpitzError::LayerWithoutWeights(index))?
+            .constraint = Some(constraint);
+        Ok(self)
+    }
+    fn set_early_stopping(&mut self, early_stopping: EarlyStopping) -> &mut Self {
+        trace!("Early stopping set : {:?}", early_stopping.monitor);
+        if let Monitor::Metric(metric) = &early_stopping.monitor {
+            if !self.metrics.contains(metric) {
+                self.metrics.push(metric.clone());
             }
-            Err(e) => panic!("`add_layer` : {}", e),
         }
+        self.early_stopping = Some(early_stopping);
+        self
+    }
+    fn set_loss(&mut self, loss: maths::Loss) -> &mut Self {
+        trace!("Loss set : {:?}", loss);
+        self.loss = loss;
+        self
+    }
+    fn set_optimizer<O: Optimizer + 'static>(&mut self, optimizer: O) -> &mut Self {
+        self.optimizer = Box::new(optimizer);
+        trace!("Optimizer set");
+        self
+    }
+    fn add_layer(
+        &mut self,
+        neurons: usize,
+        activation: maths::Activation,
+    ) -> Result<&mut Self, SpitzError> {
+        trace!(
+            "Adding layer with {:?} neurons and activation {:?}",
+            neurons,
+            activation
+        );
+        self.architecture.add_layer(neurons, activation)?;
+        Ok(self)
+    }
+    fn add_layer_with_initializer(
+        &mut self,
+        neurons: usize,
+        activation: maths::Activation,
+        initializer: maths::Initializer,
+    ) -> Result<&mut Self, SpitzError> {
+        trace!(
+            "Adding layer with {:?} neurons, activation {:?} and initializer {:?}",
+            neurons,
+            activation,
+            initializer
+        );
+        self.architecture
+            .add_layer_with_initializer(neurons, activation, Some(initializer))?;
+        Ok(self)
+    }
+    fn set_learning_rate_schedule<S: LearningRateSchedule + 'static>(
+        &mut self,
+        schedule: S,
+    ) -> &mut Self {
+        trace!("Learning rate schedule set : {:?}", schedule);
+        if let Some(Monitor::Metric(metric)) = schedule.monitor() {
+            if !self.metrics.contains(metric) {
+                self.metrics.push(metric.clone());
+            }
+        }
+        self.schedule = Box::new(schedule);
+        self
+    }
+    fn add_callback<C: Callback + 'static>(&mut self, callback: C) -> &mut Self {
+        trace!("Callback added");
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+    fn set_seed(&mut self, seed: u64) -> &mut Self {
+        trace!("Seed set : {:?}", seed);
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+    fn add<L: Layer + 'static>(&mut self, layer: L) -> &mut Self {
+        trace!("Adding layer {:?}", layer.name());
+        self.architecture.add(Box::new(layer));
+        self
+    }
+    fn add_dropout(&mut self, dropout: Dropout) -> Result<&mut Self, SpitzError> {
+        trace!("Adding dropout {:?}", dropout);
+        self.architecture.add_dropout(dropout)?;
+        Ok(self)
+    }
+    fn add_batch_norm(&mut self, batch_norm: BatchNorm) -> Result<&mut Self, SpitzError> {
+        trace!("Adding batch normalization {:?}", batch_norm);
+        self.architecture.add_batch_norm(batch_norm)?;
+        Ok(self)
+    }
+    fn set_initializer(&mut self, initializer: maths::Initializer) -> &mut Self {
+        trace!("Initializer set : {:?}", initializer);
+        self.initializer = Some(initializer);
+        self
     }
     fn input_layer(&mut self, neurons: usize) -> &mut Self {
         self.architecture.input_layer(neurons);
         trace!("Input layer set with {:?} neurons", neurons);
         self
     }
-    fn init(&mut self) -> &mut Self {
+    fn input_shape(&mut self, shape: &[usize]) -> &mut Self {
+        self.architecture.input_shape(shape);
+        trace!("Input shape set to {:?}", shape);
+        self
+    }
+    fn output_layer(&mut self, activation: maths::Activation) -> &mut Self {
+        trace!("Output layer set with activation {:?}", activation);
+        self.architecture.output_layer(activation);
+        self
+    }
+    fn init(&mut self) -> Result<&mut Self, SpitzError> {
+        // Resolve the layers sizes, inferring them from the datas when needed
+        let train_x = &self.datas_raw.train_x;
+        let train_y = &self.datas_raw.train_y;
+        let input = match self.architecture.input_layer_shape() {
+            Some(shape) => shape.to_vec(),
+            None if !train_x.is_empty() => {
+                debug!(
+                    "Input layer size inferred from training datas : {}",
+                    train_x.ncols()
+                );
+                vec![train_x.ncols()]
+            }
+            None => return Err(SpitzError::MissingInputLayer),
+        };
+        let output = if train_y.is_empty() {
+            None
+        } else {
+            Some(train_y.ncols())
+        };
+        self.architecture.build(&input, output)?;
+
+        // Verify that the architecture fits the datas
+        let input = shape_size(&input);
+        let layers = &self.architecture.layers;
+        let output = layers[layers.len() - 1].output_size();
+        check_width(&self.datas_raw.train_x, input, "training inputs")?;
+        check_width(&self.datas_raw.train_y, output, "training outputs")?;
+        check_width(&self.datas_raw.test_x, input, "testing inputs")?;
+        check_width(&self.datas_raw.test_y, output, "testing outputs")?;
+
         // Init `datas` from `datas_raw`
-        self.datas.from_datas_raw(&self.datas_raw, self.batches);
-        // Init weights
-        self.init_weights();
-        // Init grads array
-        for _ in 0..self.weights.len() {
-            self.grads.push(array![[]]);
+        self.datas = Default::default();
+        self.datas.from_datas_raw(&self.datas_raw);
+        // Init weights, excepted those just loaded by `load` if they still fit the architecture
+        if !std::mem::take(&mut self.loaded) || !self.parameters_match_architecture() {
+            self.init_weights()?;
         }
+        // Forget any previous optimizer state
+        self.optimizer.reset();
         trace!("Initiated network");
-        self
+        Ok(self)
     }
 
     fn get_architecture(&self) -> Architecture {
         self.architecture.clone()
     }
     fn get_weights(&self) -> Weights {
-        self.weights.clone()
+        let layers = self.architecture.layers.iter();
+        layers.filter_map(|l| l.weights().cloned()).collect()
+    }
+    fn get_biases(&self) -> Biases {
+        let layers = self.architecture.layers.iter();
+        layers.filter_map(|l| l.biases().cloned()).collect()
+    }
+    fn get_history(&self) -> History {
+        self.history.clone()
+    }
+}
+
+/// Returns an error if `x` and `y` do not have the same number of rows.
+pub(crate) fn check_aligned(x: &Array2<f64>, y: &Array2<f64>) -> Result<(), SpitzError> {
+    if x.nrows() != y.nrows() {
+        return Err(SpitzError::ShapeMismatch {
+            x: x.nrows(),
+            y: y.nrows(),
+        });
     }
+    Ok(())
+}
+
+/// Returns an error if imported `datas` are not `width` wide (empty datas are ignored).
+pub(crate) fn check_width(
+    datas: &Array2<f64>,
+    width: usize,
+    name: &'static str,
+) -> Result<(), SpitzError> {
+    if !datas.is_empty() && datas.ncols() != width {
+        return Err(SpitzError::IncompatibleDataWidth {
+            datas: name,
+            expected: width,
+            found: datas.ncols(),
+        });
+    }
+    Ok(())
 }
 
 /// Private callers (initializers).
 pub trait PrivateCalls {
-    /// Inits weights' matrices.
-    fn init_weights(&mut self);
+    /// Inits the parameters of every layer.
+    ///
+    /// ### Errors
+    /// Returns the error of the first layer whose parameters cannot be drawn.
+    fn init_weights(&mut self) -> Result<(), SpitzError>;
+    /// Returns `true` if every layer is built and has parameters of the shapes resolved by `build`.
+    fn parameters_match_architecture(&self) -> bool;
 }
 
 impl PrivateCalls for crate::NNetwork {
-    /// Inits weights' matrices.
-    fn init_weights(&mut self) {
-        for layer in &self.architecture.layers {
-            let m = layer.input;
-            let n = layer.size;
-
-            let w: Array2<f64> = Array::random(
-                (m, n),
-                // TODO maybe change distribution to use
-                Uniform::new(crate::WEIGHTS_INIT_MIN, crate::WEIGHTS_INIT_MAX),
-            );
-
-            self.weights.push(w);
+    fn init_weights(&mut self) -> Result<(), SpitzError> {
+        for layer in &mut self.architecture.layers {
+            // The layer initializer has priority over the global one
+            layer.init(self.initializer.as_ref(), &mut self.rng)?;
+        }
+        Ok(())
+    }
+
+    fn parameters_match_architecture(&self) -> bool {
+        let layers = &self.architecture.layers;
+        if layers.is_empty() {
+            return false;
+        }
+        let mut input = match self.architecture.input_layer_size() {
+            Some(n) => n,
+            None => layers[0].input_size(),
+        };
+        for layer in layers {
+            if input == 0 || layer.input_size() != input || !layer.is_initialized() {
+                return false;
+            }
+            input = layer.output_size();
         }
+        true
     }
 }
@@ -0,0 +1,202 @@
+use super::{shape_size, Layer};
+use crate::{Array2, Axis, Phase, SpitzError};
+use ndarray_rand::rand::RngCore;
+use std::any::Any;
+
+// * Batch normalization layer
+/// ### Batch normalization :
+/// During training, normalizes each input `x` with the mean `μ` and variance `σ²` of the batch,
+/// then scales and shifts it with the learnable parameters `γ` and `β` :\
+/// `BN(x) = γ * (x - μ) / sqrt(σ² + ε) + β`
+///
+/// The running mean and variance, updated at each batch with `running = momentum * running + (1 - momentum) * batch`,
+/// replace the batch statistics during inference.\
+/// Unlike `γ` and `β`, they are not learned by gradient descent : they are a state of the layer.
+#[derive(Clone, Debug)]
+pub struct BatchNorm {
+    pub momentum: f64,
+    pub epsilon: f64,
+    /// Learnable scale `γ`, one `(1, size)` row.
+    pub gamma: Array2<f64>,
+    /// Learnable shift `β`, one `(1, size)` row.
+    pub beta: Array2<f64>,
+    pub running_mean: Array2<f64>,
+    pub running_var: Array2<f64>,
+    shape: Vec<usize>,
+    gamma_grad: Array2<f64>,
+    beta_grad: Array2<f64>,
+    /// Datas of the last forward pass.
+    normalized: Option<Normalized>,
+}
+
+/// Datas kept from the forward pass of a `BatchNorm`, used by its backward pass.
+#[derive(Clone, Debug)]
+struct Normalized {
+    /// Normalized inputs, before the scale and shift.
+    x_hat: Array2<f64>,
+    /// Inverse of the standard deviation used, `1 / sqrt(σ² + ε)`.
+    inv_std: Array2<f64>,
+    /// Whether the statistics were those of the batch, or the running ones.
+    batch: bool,
+}
+
+impl Default for BatchNorm {
+    fn default() -> Self {
+        Self {
+            momentum: 0.99,
+            epsilon: 1e-3,
+            gamma: Array2::zeros((1, 0)),
+            beta: Array2::zeros((1, 0)),
+            running_mean: Array2::zeros((1, 0)),
+            running_var: Array2::zeros((1, 0)),
+            shape: Vec::new(),
+            gamma_grad: Array2::zeros((1, 0)),
+            beta_grad: Array2::zeros((1, 0)),
+            normalized: None,
+        }
+    }
+}
+
+impl BatchNorm {
+    /// Returns a new `BatchNorm` with `momentum = 0.99` and `epsilon = 1e-3`.\
+    /// Its parameters are sized by `init`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Use given `momentum` for the running statistics.
+    pub fn with_momentum(mut self, momentum: f64) -> Self {
+        self.momentum = momentum;
+        self
+    }
+
+    /// Add `epsilon` to the variance, to avoid dividing by zero.
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+}
+
+impl Layer for BatchNorm {
+    fn name(&self) -> &'static str {
+        "batch_norm"
+    }
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+        self.shape = input_shape.to_vec();
+        Ok(self.shape.clone())
+    }
+    fn input_shape(&self) -> Vec<usize> {
+        self.shape.clone()
+    }
+    fn output_shape(&self) -> Vec<usize> {
+        self.shape.clone()
+    }
+    /// Resets the parameters : `γ = 1`, `β = 0`, and the running statistics to a mean of `0` and a variance of `1`.
+    fn init(
+        &mut self,
+        _initializer: Option<&crate::Initializer>,
+        _rng: &mut dyn RngCore,
+    ) -> Result<(), SpitzError> {
+        let size = shape_size(&self.shape);
+        self.gamma = Array2::ones((1, size));
+        self.beta = Array2::zeros((1, size));
+        self.running_mean = Array2::zeros((1, size));
+        self.running_var = Array2::ones((1, size));
+        Ok(())
+    }
+    fn is_initialized(&self) -> bool {
+        let size = shape_size(&self.shape);
+        [
+            &self.gamma,
+            &self.beta,
+            &self.running_mean,
+            &self.running_var,
+        ]
+        .iter()
+        .all(|p| p.shape() == [1, size])
+    }
+    /// Normalizes `inputs` with the statistics of the batch in the `Phase::Training` phase
+    /// (updating the running ones), and with the running statistics else.
+    fn forward(
+        &mut self,
+        inputs: &Array2<f64>,
+        phase: Phase,
+        _rng: &mut dyn RngCore,
+    ) -> Array2<f64> {
+        let batch = phase == Phase::Training && inputs.nrows() > 0;
+        let (mean, var) = if batch {
+            let mean = inputs.mean_axis(Axis(0)).unwrap().insert_axis(Axis(0));
+            let var = (inputs - &mean)
+                .mapv(|e| e.powi(2))
+                .mean_axis(Axis(0))
+                .unwrap()
+                .insert_axis(Axis(0));
+            let momentum = self.momentum;
+            self.running_mean = &self.running_mean * momentum + &mean * (1. - momentum);
+            self.running_var = &self.running_var * momentum + &var * (1. - momentum);
+            (mean, var)
+        } else {
+            (self.running_mean.clone(), self.running_var.clone())
+        };
+
+        let inv_std = var.mapv(|v| 1. / (v + self.epsilon).sqrt());
+        let x_hat = (inputs - &mean) * &inv_std;
+        let outputs = &x_hat * &self.gamma + &self.beta;
+        self.normalized = Some(Normalized {
+            x_hat,
+            inv_std,
+            batch,
+        });
+        outputs
+    }
+    fn infer(&self, inputs: &Array2<f64>) -> Array2<f64> {
+        let inv_std = self.running_var.mapv(|v| 1. / (v + self.epsilon).sqrt());
+        (inputs - &self.running_mean) * &inv_std * &self.gamma + &self.beta
+    }
+    /// Without a previous `forward`, nothing depends on the inputs : the gradients are zeros.
+    fn backward(&mut self, grad: &Array2<f64>) -> Array2<f64> {
+        let Normalized {
+            x_hat,
+            inv_std,
+            batch,
+        } = match &self.normalized {
+            Some(normalized) => normalized,
+            None => {
+                self.gamma_grad = Array2::zeros(self.gamma.dim());
+                self.beta_grad = Array2::zeros(self.beta.dim());
+                return Array2::zeros(grad.dim());
+            }
+        };
+        self.gamma_grad = (grad * x_hat).sum_axis(Axis(0)).insert_axis(Axis(0));
+        self.beta_grad = grad.sum_axis(Axis(0)).insert_axis(Axis(0));
+
+        let x_hat_grad = grad * &self.gamma;
+        if *batch {
+            // The batch statistics depend on every sample of the batch
+            let n = grad.nrows() as f64;
+            let sum = x_hat_grad.sum_axis(Axis(0)).insert_axis(Axis(0));
+            let dot = (&x_hat_grad * x_hat).sum_axis(Axis(0)).insert_axis(Axis(0));
+            (x_hat_grad * n - sum - x_hat * &dot) * inv_std / n
+        } else {
+            x_hat_grad * inv_std
+        }
+    }
+    fn parameters(&self) -> Vec<&Array2<f64>> {
+        vec![&self.gamma, &self.beta]
+    }
+    fn parameters_mut(&mut self) -> Vec<&mut Array2<f64>> {
+        vec![&mut self.gamma, &mut self.beta]
+    }
+    fn gradients(&self) -> Vec<&Array2<f64>> {
+        vec![&self.gamma_grad, &self.beta_grad]
+    }
+    fn box_clone(&self) -> Box<dyn Layer> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,324 @@
+use super::{DataFormat, Layer, WeightsSettings};
+use crate::{Activation, Array2, Axis, Initializer, Phase, SpitzError};
+use crate::{Constraint, Regularizer};
+use ndarray_rand::rand::RngCore;
+use std::any::Any;
+
+// * 2D convolution layer
+/// 2D convolution : slides `filters` kernels over its input images, computing at each position
+/// the weighted sums `z = x · w + b` of the values under the kernel, then applies its `activation` `y = g(z)`.\
+/// Samples are images of shape `[channels, height, width]`, or `[height, width, channels]`
+/// with `DataFormat::ChannelsLast` ; the outputs keep the same format, with one channel per filter.
+///
+/// The output height is `(height + 2 * padding - dilation * (kernel - 1) - 1) / stride + 1`, likewise for the width.
+#[derive(Clone, Debug)]
+pub struct Conv2D {
+    pub filters: usize,
+    /// Height and width of the kernels.
+    pub kernel: (usize, usize),
+    /// Vertical and horizontal steps between two positions of the kernels.
+    pub stride: (usize, usize),
+    /// Rows and columns of zeros added on each side of the images.
+    pub padding: (usize, usize),
+    /// Vertical and horizontal spacing between the values under the kernels.
+    pub dilation: (usize, usize),
+    pub format: DataFormat,
+    pub activation: Activation,
+    pub settings: WeightsSettings,
+    /// `(channels, height, width)` of the inputs and of the outputs.
+    input: (usize, usize, usize),
+    output: (usize, usize, usize),
+    /// For each output position and kernel value, index of the input value under it (`None` in the padding).
+    indices: Vec<Option<usize>>,
+    /// One row per kernel value (`channels * kernel height * kernel width` rows), one column per filter.
+    weights: Array2<f64>,
+    biases: Array2<f64>,
+    weights_grad: Array2<f64>,
+    biases_grad: Array2<f64>,
+    /// Values under the kernels at each position, weighted sums and outputs of the last forward pass.
+    columns: Array2<f64>,
+    weighted_sums: Array2<f64>,
+    outputs: Array2<f64>,
+}
+
+impl Conv2D {
+    /// Returns a new `Conv2D` layer of `filters` kernels of size `kernel`, with given `activation`.\
+    /// It has a stride of `1`, no padding nor dilation, and takes `DataFormat::ChannelsFirst` images.
+    pub fn new(filters: usize, kernel: (usize, usize), activation: Activation) -> Self {
+        Self {
+            filters,
+            kernel,
+            stride: (1, 1),
+            padding: (0, 0),
+            dilation: (1, 1),
+            format: Default::default(),
+            activation,
+            settings: Default::default(),
+            input: (0, 0, 0),
+            output: (0, 0, 0),
+            indices: Vec::new(),
+            weights: Array2::zeros((0, 0)),
+            biases: Array2::zeros((0, 0)),
+            weights_grad: Array2::zeros((0, 0)),
+            biases_grad: Array2::zeros((0, 0)),
+            columns: Array2::zeros((0, 0)),
+            weighted_sums: Array2::zeros((0, 0)),
+            outputs: Array2::zeros((0, 0)),
+        }
+    }
+
+    /// Use given `stride`.
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    /// Use given zero `padding`.
+    pub fn with_padding(mut self, padding: (usize, usize)) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Use given `dilation`.
+    pub fn with_dilation(mut self, dilation: (usize, usize)) -> Self {
+        self.dilation = dilation;
+        self
+    }
+
+    /// Take and return images in given `format`.
+    pub fn with_format(mut self, format: DataFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Draw the weights with given `initializer`, instead of the one of the network.
+    pub fn with_initializer(mut self, initializer: Initializer) -> Self {
+        self.settings.initializer = Some(initializer);
+        self
+    }
+
+    /// Penalize the weights with given `regularizer`, instead of the one of the network.
+    pub fn with_regularizer(mut self, regularizer: Regularizer) -> Self {
+        self.settings.regularizer = Some(regularizer);
+        self
+    }
+
+    /// Constrain the weights with given `constraint`, instead of the one of the network.
+    pub fn with_constraint(mut self, constraint: Constraint) -> Self {
+        self.settings.constraint = Some(constraint);
+        self
+    }
+
+    /// Returns the number of weights of each filter, `channels * kernel height * kernel width`.
+    fn kernel_size(&self) -> usize {
+        self.input.0 * self.kernel.0 * self.kernel.1
+    }
+
+    /// Returns the number of positions of the kernels, `output height * output width`.
+    fn positions(&self) -> usize {
+        self.output.1 * self.output.2
+    }
+
+    /// Returns the values under the kernels : one row per sample and position, one column per kernel value.
+    fn columns(&self, inputs: &Array2<f64>) -> Array2<f64> {
+        let (positions, size) = (self.positions(), self.kernel_size());
+        Array2::from_shape_fn(
+            (inputs.nrows() * positions, size),
+            |(row, col)| match self.indices[(row % positions) * size + col] {
+                Some(index) => inputs[[row / positions, index]],
+                None => 0.,
+            },
+        )
+    }
+
+    /// Returns the weighted sums of given `columns`, one flattened image per sample.
+    fn weighted_sums(&self, columns: &Array2<f64>) -> Array2<f64> {
+        let sums = columns.dot(&self.weights) + &self.biases;
+        let positions = self.positions();
+        let mut images = Array2::zeros((sums.nrows() / positions, positions * self.filters));
+        for ((row, filter), &sum) in sums.indexed_iter() {
+            images[[row / positions, self.output_index(filter, row % positions)]] = sum;
+        }
+        images
+    }
+
+    /// Returns the index, in a flattened output image, of given `filter` at given `position`.
+    fn output_index(&self, filter: usize, position: usize) -> usize {
+        let width = self.output.2;
+        self.format
+            .index(self.output, filter, position / width, position % width)
+    }
+}
+
+impl Layer for Conv2D {
+    fn name(&self) -> &'static str {
+        "conv2d"
+    }
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+        let incompatible = || SpitzError::IncompatibleShape {
+            layer: "conv2d",
+            shape: input_shape.to_vec(),
+        };
+        if self.filters == 0
+            || [self.kernel, self.stride, self.dilation]
+                .iter()
+                .any(|&(h, w)| h == 0 || w == 0)
+        {
+            return Err(SpitzError::InvalidLayerSize);
+        }
+        let (channels, height, width) = self.format.dims(input_shape).ok_or_else(incompatible)?;
+
+        // Size of the kernels once dilated, and of the images once padded
+        let span = |kernel: usize, dilation: usize| dilation * (kernel - 1) + 1;
+        let (span_h, span_w) = (
+            span(self.kernel.0, self.dilation.0),
+            span(self.kernel.1, self.dilation.1),
+        );
+        let (padded_h, padded_w) = (height + 2 * self.padding.0, width + 2 * self.padding.1);
+        if channels == 0 || padded_h < span_h || padded_w < span_w {
+            return Err(incompatible());
+        }
+        self.input = (channels, height, width);
+        self.output = (
+            self.filters,
+            (padded_h - span_h) / self.stride.0 + 1,
+            (padded_w - span_w) / self.stride.1 + 1,
+        );
+
+        // Index of the input value under each kernel value, at each position
+        self.indices = Vec::with_capacity(self.positions() * self.kernel_size());
+        for out_y in 0..self.output.1 {
+            for out_x in 0..self.output.2 {
+                for c in 0..channels {
+                    for i in 0..self.kernel.0 {
+                        for j in 0..self.kernel.1 {
+                            let y = (out_y * self.stride.0 + i * self.dilation.0)
+                                .checked_sub(self.padding.0)
+                                .filter(|&y| y < height);
+                            let x = (out_x * self.stride.1 + j * self.dilation.1)
+                                .checked_sub(self.padding.1)
+                                .filter(|&x| x < width);
+                            self.indices.push(match (y, x) {
+                                (Some(y), Some(x)) => Some(self.format.index(self.input, c, y, x)),
+                                _ => None,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Ok(self.format.shape(self.output))
+    }
+    fn input_shape(&self) -> Vec<usize> {
+        match self.input {
+            (0, 0, 0) => Vec::new(),
+            input => self.format.shape(input),
+        }
+    }
+    fn output_shape(&self) -> Vec<usize> {
+        match self.output {
+            (0, 0, 0) => Vec::new(),
+            output => self.format.shape(output),
+        }
+    }
+    fn init(
+        &mut self,
+        initializer: Option<&Initializer>,
+        rng: &mut dyn RngCore,
+    ) -> Result<(), SpitzError> {
+        let initializer = self.settings.initializer(initializer, &self.activation);
+        self.weights = initializer.weights(self.kernel_size(), self.filters, rng)?;
+        self.biases = Array2::zeros((1, self.filters));
+        Ok(())
+    }
+    fn is_initialized(&self) -> bool {
+        self.weights.shape() == [self.kernel_size(), self.filters]
+            && self.biases.shape() == [1, self.filters]
+    }
+    fn forward(
+        &mut self,
+        inputs: &Array2<f64>,
+        _phase: Phase,
+        _rng: &mut dyn RngCore,
+    ) -> Array2<f64> {
+        self.columns = self.columns(inputs);
+        let z = self.weighted_sums(&self.columns);
+        let outputs = self.activation.compute(z.clone(), false);
+        self.weighted_sums = z;
+        self.outputs = outputs.clone();
+        outputs
+    }
+    fn infer(&self, inputs: &Array2<f64>) -> Array2<f64> {
+        let z = self.weighted_sums(&self.columns(inputs));
+        self.activation.compute(z, false)
+    }
+    fn backward(&mut self, grad: &Array2<f64>) -> Array2<f64> {
+        let delta = self
+            .activation
+            .backward(&self.weighted_sums, &self.outputs, grad.clone());
+
+        // Back to one row per sample and position, one column per filter
+        let positions = self.positions();
+        let mut sums_grad = Array2::zeros((delta.nrows() * positions, self.filters));
+        for ((row, filter), value) in sums_grad.indexed_iter_mut() {
+            *value = delta[[row / positions, self.output_index(filter, row % positions)]];
+        }
+        self.weights_grad = self.columns.t().dot(&sums_grad);
+        self.biases_grad = sums_grad.sum_axis(Axis(0)).insert_axis(Axis(0));
+
+        // Each input value receives the gradients of every kernel value that was over it
+        let columns_grad = sums_grad.dot(&self.weights.t());
+        let size = self.kernel_size();
+        let (channels, height, width) = self.input;
+        let mut inputs_grad = Array2::zeros((delta.nrows(), channels * height * width));
+        for ((row, col), &value) in columns_grad.indexed_iter() {
+            if let Some(index) = self.indices[(row % positions) * size + col] {
+                inputs_grad[[row / positions, index]] += value;
+            }
+        }
+        inputs_grad
+    }
+    fn parameters(&self) -> Vec<&Array2<f64>> {
+        vec![&self.weights, &self.biases]
+    }
+    fn parameters_mut(&mut self) -> Vec<&mut Array2<f64>> {
+        vec![&mut self.weights, &mut self.biases]
+    }
+    fn gradients(&self) -> Vec<&Array2<f64>> {
+        vec![&self.weights_grad, &self.biases_grad]
+    }
+    fn weights_index(&self) -> Option<usize> {
+        Some(0)
+    }
+    fn activation(&self) -> Option<&Activation> {
+        Some(&self.activation)
+    }
+    fn weights(&self) -> Option<&Array2<f64>> {
+        Some(&self.weights)
+    }
+    fn biases(&self) -> Option<&Array2<f64>> {
+        Some(&self.biases)
+    }
+    fn settings(&self) -> Option<&WeightsSettings> {
+        Some(&self.settings)
+    }
+    fn settings_mut(&mut self) -> Option<&mut WeightsSettings> {
+        Some(&mut self.settings)
+    }
+    fn regularize(&mut self, regularizer: Option<&Regularizer>) -> f64 {
+        self.settings
+            .regularize(regularizer, &self.weights, &mut self.weights_grad)
+    }
+    fn constrain(&mut self, constraint: Option<&Constraint>) {
+        self.settings.constrain(constraint, &mut self.weights);
+    }
+    fn box_clone(&self) -> Box<dyn Layer> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,176 @@
+use super::{shape_size, Layer, WeightsSettings};
+use crate::{Activation, Array2, Axis, Initializer, Phase, SpitzError};
+use crate::{Constraint, Regularizer};
+use ndarray_rand::rand::RngCore;
+use std::any::Any;
+
+// * Dense layer
+/// Fully connected layer : computes the weighted sums `z = x · w + b` of its inputs,
+/// then applies its `activation` `y = g(z)`.\
+/// Its samples are flattened if they have more than one dimension.
+#[derive(Clone, Debug)]
+pub struct Dense {
+    pub size: usize,
+    pub activation: Activation,
+    pub settings: WeightsSettings,
+    input: usize,
+    weights: Array2<f64>,
+    /// One `(1, size)` row, broadcasted over every sample of a batch.
+    biases: Array2<f64>,
+    weights_grad: Array2<f64>,
+    biases_grad: Array2<f64>,
+    /// Inputs, weighted sums and outputs of the last forward pass.
+    inputs: Array2<f64>,
+    weighted_sums: Array2<f64>,
+    outputs: Array2<f64>,
+}
+
+impl Dense {
+    /// Returns a new `Dense` layer of `size` neurons, with given `activation`.
+    pub fn new(size: usize, activation: Activation) -> Self {
+        Self {
+            size,
+            activation,
+            settings: Default::default(),
+            input: 0,
+            weights: Array2::zeros((0, 0)),
+            biases: Array2::zeros((0, 0)),
+            weights_grad: Array2::zeros((0, 0)),
+            biases_grad: Array2::zeros((0, 0)),
+            inputs: Array2::zeros((0, 0)),
+            weighted_sums: Array2::zeros((0, 0)),
+            outputs: Array2::zeros((0, 0)),
+        }
+    }
+
+    /// Draw the weights with given `initializer`, instead of the one of the network.
+    pub fn with_initializer(mut self, initializer: Initializer) -> Self {
+        self.settings.initializer = Some(initializer);
+        self
+    }
+
+    /// Penalize the weights with given `regularizer`, instead of the one of the network.
+    pub fn with_regularizer(mut self, regularizer: Regularizer) -> Self {
+        self.settings.regularizer = Some(regularizer);
+        self
+    }
+
+    /// Constrain the weights with given `constraint`, instead of the one of the network.
+    pub fn with_constraint(mut self, constraint: Constraint) -> Self {
+        self.settings.constraint = Some(constraint);
+        self
+    }
+
+    /// Returns the weighted sums `z = x · w + b` of `inputs`.
+    fn weighted_sums(&self, inputs: &Array2<f64>) -> Array2<f64> {
+        inputs.dot(&self.weights) + &self.biases
+    }
+
+    /// Backpropagates `delta`, the derivative of the loss with respect to the weighted sums of the last forward pass.\
+    /// Used to skip the activation when its derivative is fused with the loss one.
+    pub(crate) fn backward_weighted_sums(&mut self, delta: &Array2<f64>) -> Array2<f64> {
+        self.weights_grad = self.inputs.t().dot(delta);
+        self.biases_grad = delta.sum_axis(Axis(0)).insert_axis(Axis(0));
+        delta.dot(&self.weights.t())
+    }
+}
+
+impl Layer for Dense {
+    fn name(&self) -> &'static str {
+        "dense"
+    }
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+        self.input = shape_size(input_shape);
+        Ok(vec![self.size])
+    }
+    fn input_shape(&self) -> Vec<usize> {
+        match self.input {
+            0 => Vec::new(),
+            input => vec![input],
+        }
+    }
+    fn output_shape(&self) -> Vec<usize> {
+        vec![self.size]
+    }
+    fn init(
+        &mut self,
+        initializer: Option<&Initializer>,
+        rng: &mut dyn RngCore,
+    ) -> Result<(), SpitzError> {
+        let initializer = self.settings.initializer(initializer, &self.activation);
+        self.weights = initializer.weights(self.input, self.size, rng)?;
+        // Biases start at zero, so that the initial output only depends on the weights
+        self.biases = Array2::zeros((1, self.size));
+        Ok(())
+    }
+    fn is_initialized(&self) -> bool {
+        self.weights.shape() == [self.input, self.size] && self.biases.shape() == [1, self.size]
+    }
+    fn forward(
+        &mut self,
+        inputs: &Array2<f64>,
+        _phase: Phase,
+        _rng: &mut dyn RngCore,
+    ) -> Array2<f64> {
+        let z = self.weighted_sums(inputs);
+        let outputs = self.activation.compute(z.clone(), false);
+        self.inputs = inputs.clone();
+        self.weighted_sums = z;
+        self.outputs = outputs.clone();
+        outputs
+    }
+    fn infer(&self, inputs: &Array2<f64>) -> Array2<f64> {
+        // Activation function `y = g(z)` of the weighted sums
+        self.activation.compute(self.weighted_sums(inputs), false)
+    }
+    fn backward(&mut self, grad: &Array2<f64>) -> Array2<f64> {
+        // The derivative of the activation is taken at the weighted sums `z`
+        let delta = self
+            .activation
+            .backward(&self.weighted_sums, &self.outputs, grad.clone());
+        self.backward_weighted_sums(&delta)
+    }
+    fn parameters(&self) -> Vec<&Array2<f64>> {
+        vec![&self.weights, &self.biases]
+    }
+    fn parameters_mut(&mut self) -> Vec<&mut Array2<f64>> {
+        vec![&mut self.weights, &mut self.biases]
+    }
+    fn gradients(&self) -> Vec<&Array2<f64>> {
+        vec![&self.weights_grad, &self.biases_grad]
+    }
+    fn weights_index(&self) -> Option<usize> {
+        Some(0)
+    }
+    fn activation(&self) -> Option<&Activation> {
+        Some(&self.activation)
+    }
+    fn weights(&self) -> Option<&Array2<f64>> {
+        Some(&self.weights)
+    }
+    fn biases(&self) -> Option<&Array2<f64>> {
+        Some(&self.biases)
+    }
+    fn settings(&self) -> Option<&WeightsSettings> {
+        Some(&self.settings)
+    }
+    fn settings_mut(&mut self) -> Option<&mut WeightsSettings> {
+        Some(&mut self.settings)
+    }
+    fn regularize(&mut self, regularizer: Option<&Regularizer>) -> f64 {
+        self.settings
+            .regularize(regularizer, &self.weights, &mut self.weights_grad)
+    }
+    fn constrain(&mut self, constraint: Option<&Constraint>) {
+        self.settings.constrain(constraint, &mut self.weights);
+    }
+    fn box_clone(&self) -> Box<dyn Layer> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,69 @@
+use super::Layer;
+use crate::{Array2, Dropout, Phase, SpitzError};
+use ndarray_rand::rand::RngCore;
+use std::any::Any;
+
+// * Dropout layer
+/// Applies its `dropout` to the outputs of the previous layer, during training only.
+#[derive(Clone, Debug)]
+pub struct DropoutLayer {
+    pub dropout: Dropout,
+    shape: Vec<usize>,
+    /// Derivative of the last forward pass, if the dropout was applied.
+    derivative: Option<Array2<f64>>,
+}
+
+impl DropoutLayer {
+    /// Returns a new `DropoutLayer` applying `dropout`.
+    pub fn new(dropout: Dropout) -> Self {
+        Self {
+            dropout,
+            shape: Vec::new(),
+            derivative: None,
+        }
+    }
+}
+
+impl Layer for DropoutLayer {
+    fn name(&self) -> &'static str {
+        "dropout"
+    }
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+        self.shape = input_shape.to_vec();
+        Ok(self.shape.clone())
+    }
+    fn input_shape(&self) -> Vec<usize> {
+        self.shape.clone()
+    }
+    fn output_shape(&self) -> Vec<usize> {
+        self.shape.clone()
+    }
+    fn forward(
+        &mut self,
+        inputs: &Array2<f64>,
+        phase: Phase,
+        rng: &mut dyn RngCore,
+    ) -> Array2<f64> {
+        let (outputs, derivative) = self.dropout.forward(inputs.clone(), phase, rng);
+        self.derivative = derivative;
+        outputs
+    }
+    fn infer(&self, inputs: &Array2<f64>) -> Array2<f64> {
+        inputs.clone()
+    }
+    fn backward(&mut self, grad: &Array2<f64>) -> Array2<f64> {
+        match &self.derivative {
+            Some(derivative) => grad * derivative,
+            None => grad.clone(),
+        }
+    }
+    fn box_clone(&self) -> Box<dyn Layer> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
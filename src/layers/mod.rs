@@ -0,0 +1,265 @@
+//! ### Layers
+//! Provides the `Layer` trait, implemented by every part of the architecture that transforms the datas
+//! flowing through the network, and its implementations :
+//! - `Dense`, a fully connected layer followed by an activation ;
+//! - `Conv2D`, a 2D convolution of images followed by an activation ;
+//! - `MaxPool2D`, `AvgPool2D`, `GlobalMaxPooling2D` and `GlobalAveragePooling2D`, that pool images ;
+//! - `DropoutLayer`, that applies a `Dropout` during training ;
+//! - `PRelu`, a leaky relu whose slopes are trained ;
+//! - `BatchNorm`, that normalizes the outputs of the previous layer over each batch.
+//!
+//! Datas are given to layers as batches of flattened samples, one sample per row ;
+//! the shape of a sample (e.g. `[size]`, or `[channels, height, width]`) is resolved by `build`.\
+//! Images are flattened following their `DataFormat`.
+//!
+//! Custom layers implement `Layer` and are added to the network with `add`.
+
+pub mod batch_norm;
+pub mod conv;
+pub mod dense;
+pub mod dropout;
+pub mod pooling;
+pub mod prelu;
+
+pub use batch_norm::BatchNorm;
+pub use conv::Conv2D;
+pub use dense::Dense;
+pub use dropout::DropoutLayer;
+pub use pooling::{AvgPool2D, GlobalAveragePooling2D, GlobalMaxPooling2D, MaxPool2D};
+pub use prelu::PRelu;
+
+use crate::{Array2, Constraint, Initializer, Phase, Regularizer, SpitzError};
+use ndarray_rand::rand::RngCore;
+use std::any::Any;
+use std::fmt;
+
+/// A layer of the network.\
+/// `forward` keeps what `backward` needs, so `backward` always refers to the last call to `forward`.
+pub trait Layer: fmt::Debug {
+    /// Returns the name of the layer, used to save networks.
+    fn name(&self) -> &'static str;
+    /// Resolves the shape of the samples given to the layer, from the output shape of the previous one,
+    /// and returns the shape of its outputs.
+    ///
+    /// ### Errors
+    /// Returns an error if the layer cannot take samples of shape `input_shape`.
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError>;
+    /// Returns the shape of the samples given to the layer, empty until built.
+    fn input_shape(&self) -> Vec<usize>;
+    /// Returns the shape of the outputs of the layer, for one sample (empty if unknown until built).
+    fn output_shape(&self) -> Vec<usize>;
+    /// Returns the number of values of a sample given to the layer.
+    fn input_size(&self) -> usize {
+        shape_size(&self.input_shape())
+    }
+    /// Returns the number of values of an output of the layer.
+    fn output_size(&self) -> usize {
+        shape_size(&self.output_shape())
+    }
+
+    /// Inits the parameters of a built layer, drawing them with `rng`.\
+    /// `initializer` is the one set on the network, if any.
+    ///
+    /// ### Errors
+    /// Returns an error if the parameters cannot be drawn, e.g. with an invalid initializer.
+    fn init(
+        &mut self,
+        _initializer: Option<&Initializer>,
+        _rng: &mut dyn RngCore,
+    ) -> Result<(), SpitzError> {
+        Ok(())
+    }
+    /// Returns `true` if the parameters of the layer fit the shapes resolved by `build`.
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    /// Runs the layer on a batch of `inputs` in given `phase`, drawing random values with `rng`.
+    fn forward(&mut self, inputs: &Array2<f64>, phase: Phase, rng: &mut dyn RngCore)
+        -> Array2<f64>;
+    /// Runs the layer on a batch of `inputs` in the `Phase::Inference` phase, without keeping anything.
+    fn infer(&self, inputs: &Array2<f64>) -> Array2<f64>;
+    /// Backpropagates `grad`, the derivative of the loss with respect to the outputs of the last `forward`.\
+    /// Keeps the gradients of the parameters, and returns the derivative of the loss with respect to the inputs.
+    fn backward(&mut self, grad: &Array2<f64>) -> Array2<f64>;
+
+    /// Returns the parameters trained by the optimizer.
+    fn parameters(&self) -> Vec<&Array2<f64>> {
+        Vec::new()
+    }
+    /// Returns the parameters trained by the optimizer, in the same order as `parameters`.
+    fn parameters_mut(&mut self) -> Vec<&mut Array2<f64>> {
+        Vec::new()
+    }
+    /// Returns the gradients computed by the last `backward`, in the same order as `parameters`.
+    fn gradients(&self) -> Vec<&Array2<f64>> {
+        Vec::new()
+    }
+    /// Returns the index of the weights in `parameters`, if the layer has some.\
+    /// The weights are the only parameters subject to weight decay (see `Optimizer::update_weights`).
+    fn weights_index(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the activation applied to the outputs of the layer, if any.
+    fn activation(&self) -> Option<&crate::Activation> {
+        None
+    }
+    /// Returns the weights of the layer, if it has some.
+    fn weights(&self) -> Option<&Array2<f64>> {
+        None
+    }
+    /// Returns the biases of the layer, if it has some.
+    fn biases(&self) -> Option<&Array2<f64>> {
+        None
+    }
+    /// Returns the settings of the weights of the layer, if it has some.
+    fn settings(&self) -> Option<&WeightsSettings> {
+        None
+    }
+    /// Returns the settings of the weights of the layer, if it has some.
+    fn settings_mut(&mut self) -> Option<&mut WeightsSettings> {
+        None
+    }
+    /// Adds the derivative of the weights penalty to their gradient, and returns the penalty.\
+    /// `regularizer` is the one set on the network, if any.
+    fn regularize(&mut self, _regularizer: Option<&Regularizer>) -> f64 {
+        0.
+    }
+    /// Applies the constraint of the weights after an update.\
+    /// `constraint` is the one set on the network, if any.
+    fn constrain(&mut self, _constraint: Option<&Constraint>) {}
+
+    /// Returns a boxed copy of the layer, used to clone the network.
+    fn box_clone(&self) -> Box<dyn Layer>;
+    /// Returns the layer as `Any`, to downcast it to its concrete type.
+    fn as_any(&self) -> &dyn Any;
+    /// Returns the layer as `Any`, to downcast it to its concrete type.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl Clone for Box<dyn Layer> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Returns the number of values of a sample of given `shape`, or `0` if it is unknown.
+pub fn shape_size(shape: &[usize]) -> usize {
+    if shape.is_empty() {
+        0
+    } else {
+        shape.iter().product()
+    }
+}
+
+// * Data format
+/// Layout of the values of an image sample, flattened in a row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DataFormat {
+    /// Shape `[channels, height, width]`, the layout of the samples of NCHW tensors.
+    #[default]
+    ChannelsFirst,
+    /// Shape `[height, width, channels]`, the layout of the samples of NHWC tensors.
+    ChannelsLast,
+}
+
+impl DataFormat {
+    /// Returns the `(channels, height, width)` dimensions of an image of given `shape`,
+    /// if it has three dimensions.
+    pub fn dims(self, shape: &[usize]) -> Option<(usize, usize, usize)> {
+        match (self, shape) {
+            (DataFormat::ChannelsFirst, &[c, h, w]) => Some((c, h, w)),
+            (DataFormat::ChannelsLast, &[h, w, c]) => Some((c, h, w)),
+            _ => None,
+        }
+    }
+
+    /// Returns the shape of an image of given `(channels, height, width)` dimensions.
+    pub fn shape(self, (c, h, w): (usize, usize, usize)) -> Vec<usize> {
+        match self {
+            DataFormat::ChannelsFirst => vec![c, h, w],
+            DataFormat::ChannelsLast => vec![h, w, c],
+        }
+    }
+
+    /// Returns the index of the value of channel `c` at row `y` and column `x`,
+    /// in a flattened image of given `(channels, height, width)` dimensions.
+    pub fn index(
+        self,
+        (channels, height, width): (usize, usize, usize),
+        c: usize,
+        y: usize,
+        x: usize,
+    ) -> usize {
+        match self {
+            DataFormat::ChannelsFirst => (c * height + y) * width + x,
+            DataFormat::ChannelsLast => (y * width + x) * channels + c,
+        }
+    }
+
+    /// Returns the name of the format, used to save networks.
+    pub fn name(self) -> &'static str {
+        match self {
+            DataFormat::ChannelsFirst => "channels_first",
+            DataFormat::ChannelsLast => "channels_last",
+        }
+    }
+
+    /// Returns the format called `name`, if it exists.
+    pub fn from_name(name: &str) -> Option<DataFormat> {
+        match name {
+            "channels_first" => Some(DataFormat::ChannelsFirst),
+            "channels_last" => Some(DataFormat::ChannelsLast),
+            _ => None,
+        }
+    }
+}
+
+// * Weights settings
+/// Settings of the weights of a layer : their `initializer`, `regularizer` and `constraint`.\
+/// Those not set fall back on the ones set on the network.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WeightsSettings {
+    pub initializer: Option<Initializer>,
+    pub regularizer: Option<Regularizer>,
+    pub constraint: Option<Constraint>,
+}
+
+impl WeightsSettings {
+    /// Returns the initializer to use : the own one, else `initializer`, else the one suiting `activation`.
+    pub fn initializer(
+        &self,
+        initializer: Option<&Initializer>,
+        activation: &crate::Activation,
+    ) -> Initializer {
+        match self.initializer.as_ref().or(initializer) {
+            Some(initializer) => initializer.clone(),
+            None => Initializer::for_activation(activation),
+        }
+    }
+
+    /// Adds to `grad` the derivative of the penalty of `weights`, and returns the penalty.\
+    /// The own regularizer is used, else `regularizer`.
+    pub fn regularize(
+        &self,
+        regularizer: Option<&Regularizer>,
+        weights: &Array2<f64>,
+        grad: &mut Array2<f64>,
+    ) -> f64 {
+        match self.regularizer.as_ref().or(regularizer) {
+            Some(regularizer) => {
+                *grad += &regularizer.derivative(weights);
+                regularizer.penalty(weights)
+            }
+            None => 0.,
+        }
+    }
+
+    /// Applies to `weights` the own constraint, else `constraint`.
+    pub fn constrain(&self, constraint: Option<&Constraint>, weights: &mut Array2<f64>) {
+        if let Some(constraint) = self.constraint.as_ref().or(constraint) {
+            constraint.apply(weights);
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use super::{DataFormat, Layer};
+use crate::{Array2, Phase, SpitzError};
+use ndarray_rand::rand::RngCore;
+use std::any::Any;
+
+// * Pooling
+/// Reduces windows of the input images to one value each, shared by the pooling layers.
+#[derive(Clone, Debug, Default)]
+struct Pooling {
+    /// Whether the windows are reduced to their maximum, or to their average.
+    max: bool,
+    input_shape: Vec<usize>,
+    output_shape: Vec<usize>,
+    /// Indices of the input values of each window, in the order of the flattened outputs.
+    windows: Vec<Vec<usize>>,
+    /// Index of the maximum of each window at the last forward pass, for each sample.
+    argmax: Vec<usize>,
+}
+
+impl Pooling {
+    fn new(max: bool) -> Self {
+        Self {
+            max,
+            ..Default::default()
+        }
+    }
+
+    /// Splits the images of given `shape` in windows of size `pool`, every `stride`.
+    fn build(
+        &mut self,
+        layer: &'static str,
+        shape: &[usize],
+        format: DataFormat,
+        pool: (usize, usize),
+        stride: (usize, usize),
+    ) -> Result<Vec<usize>, SpitzError> {
+        if [pool, stride].iter().any(|&(h, w)| h == 0 || w == 0) {
+            return Err(SpitzError::InvalidLayerSize);
+        }
+        let input = match format.dims(shape) {
+            Some((c, h, w)) if c > 0 && h >= pool.0 && w >= pool.1 => (c, h, w),
+            _ => {
+                return Err(SpitzError::IncompatibleShape {
+                    layer,
+                    shape: shape.to_vec(),
+                })
+            }
+        };
+        let (channels, height, width) = input;
+        let output = (
+            channels,
+            (height - pool.0) / stride.0 + 1,
+            (width - pool.1) / stride.1 + 1,
+        );
+
+        self.windows = vec![Vec::with_capacity(pool.0 * pool.1); channels * output.1 * output.2];
+        for c in 0..channels {
+            for out_y in 0..output.1 {
+                for out_x in 0..output.2 {
+                    let window = &mut self.windows[format.index(output, c, out_y, out_x)];
+                    for y in out_y * stride.0..out_y * stride.0 + pool.0 {
+                        for x in out_x * stride.1..out_x * stride.1 + pool.1 {
+                            window.push(format.index(input, c, y, x));
+                        }
+                    }
+                }
+            }
+        }
+        self.input_shape = shape.to_vec();
+        self.output_shape = format.shape(output);
+        Ok(self.output_shape.clone())
+    }
+
+    /// Makes one window per channel of the images of given `shape`, holding the whole channel.
+    fn build_global(
+        &mut self,
+        layer: &'static str,
+        shape: &[usize],
+        format: DataFormat,
+    ) -> Result<Vec<usize>, SpitzError> {
+        let (channels, height, width) = match format.dims(shape) {
+            Some((c, h, w)) if c > 0 && h > 0 && w > 0 => (c, h, w),
+            _ => {
+                return Err(SpitzError::IncompatibleShape {
+                    layer,
+                    shape: shape.to_vec(),
+                })
+            }
+        };
+        self.windows = (0..channels)
+            .map(|c| {
+                (0..height * width)
+                    .map(|i| format.index((channels, height, width), c, i / width, i % width))
+                    .collect()
+            })
+            .collect();
+        self.input_shape = shape.to_vec();
+        self.output_shape = vec![channels];
+        Ok(self.output_shape.clone())
+    }
+
+    /// Returns the pooled `inputs`, and the index of the maximum of each window for max pooling.
+    fn pool(&self, inputs: &Array2<f64>) -> (Array2<f64>, Vec<usize>) {
+        let mut outputs = Array2::zeros((inputs.nrows(), self.windows.len()));
+        let mut argmax = Vec::new();
+        for (sample, mut row) in inputs.genrows().into_iter().zip(outputs.genrows_mut()) {
+            for (output, window) in row.iter_mut().zip(&self.windows) {
+                if self.max {
+                    let index = window.iter().copied().fold(window[0], |m, i| {
+                        if sample[i] > sample[m] {
+                            i
+                        } else {
+                            m
+                        }
+                    });
+                    argmax.push(index);
+                    *output = sample[index];
+                } else {
+                    *output = window.iter().map(|&i| sample[i]).sum::<f64>() / window.len() as f64;
+                }
+            }
+        }
+        (outputs, argmax)
+    }
+
+    fn forward(&mut self, inputs: &Array2<f64>) -> Array2<f64> {
+        let (outputs, argmax) = self.pool(inputs);
+        self.argmax = argmax;
+        outputs
+    }
+
+    /// Routes the gradient of each output to the maximum of its window for max pooling,
+    /// or spreads it evenly over its window for average pooling.
+    fn backward(&self, grad: &Array2<f64>) -> Array2<f64> {
+        let size: usize = self.input_shape.iter().product();
+        let mut inputs_grad = Array2::zeros((grad.nrows(), size));
+        let outputs = self.windows.len();
+        for ((sample, output), &value) in grad.indexed_iter() {
+            if self.max {
+                inputs_grad[[sample, self.argmax[sample * outputs + output]]] += value;
+            } else {
+                let window = &self.windows[output];
+                for &i in window {
+                    inputs_grad[[sample, i]] += value / window.len() as f64;
+                }
+            }
+        }
+        inputs_grad
+    }
+}
+
+/// Implements `Layer` for a pooling layer named `$name`, holding a `pooling` built by its `build_pooling` method.
+macro_rules! pooling_layer {
+    ($layer:ty, $name:expr) => {
+        impl Layer for $layer {
+            fn name(&self) -> &'static str {
+                $name
+            }
+            fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+                self.build_pooling(input_shape)
+            }
+            fn input_shape(&self) -> Vec<usize> {
+                self.pooling.input_shape.clone()
+            }
+            fn output_shape(&self) -> Vec<usize> {
+                self.pooling.output_shape.clone()
+            }
+            fn forward(
+                &mut self,
+                inputs: &Array2<f64>,
+                _phase: Phase,
+                _rng: &mut dyn RngCore,
+            ) -> Array2<f64> {
+                self.pooling.forward(inputs)
+            }
+            fn infer(&self, inputs: &Array2<f64>) -> Array2<f64> {
+                self.pooling.pool(inputs).0
+            }
+            fn backward(&mut self, grad: &Array2<f64>) -> Array2<f64> {
+                self.pooling.backward(grad)
+            }
+            fn box_clone(&self) -> Box<dyn Layer> {
+                Box::new(self.clone())
+            }
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+            fn as_any_mut(&mut self) -> &mut dyn Any {
+                self
+            }
+        }
+    };
+}
+
+// * Max pooling layer
+/// Max pooling : reduces each `pool` window of the input images to its maximum,
+/// moving the window by `stride` (by default, the size of the window).\
+/// During backpropagation, the gradient of each output goes to the maximum of its window only.
+#[derive(Clone, Debug)]
+pub struct MaxPool2D {
+    /// Height and width of the windows.
+    pub pool: (usize, usize),
+    /// Vertical and horizontal steps between two windows.
+    pub stride: (usize, usize),
+    pub format: DataFormat,
+    pooling: Pooling,
+}
+
+impl MaxPool2D {
+    /// Returns a new `MaxPool2D` layer, of windows of size `pool` that do not overlap.
+    pub fn new(pool: (usize, usize)) -> Self {
+        Self {
+            pool,
+            stride: pool,
+            format: Default::default(),
+            pooling: Pooling::new(true),
+        }
+    }
+
+    /// Use given `stride`.
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    /// Take and return images in given `format`.
+    pub fn with_format(mut self, format: DataFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Splits the images of given `input_shape` in windows, for `Layer::build`.
+    fn build_pooling(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+        self.pooling.build(
+            self.name(),
+            input_shape,
+            self.format,
+            self.pool,
+            self.stride,
+        )
+    }
+}
+
+pooling_layer!(MaxPool2D, "max_pool2d");
+
+// * Average pooling layer
+/// Average pooling : reduces each `pool` window of the input images to its average,
+/// moving the window by `stride` (by default, the size of the window).
+#[derive(Clone, Debug)]
+pub struct AvgPool2D {
+    /// Height and width of the windows.
+    pub pool: (usize, usize),
+    /// Vertical and horizontal steps between two windows.
+    pub stride: (usize, usize),
+    pub format: DataFormat,
+    pooling: Pooling,
+}
+
+impl AvgPool2D {
+    /// Returns a new `AvgPool2D` layer, of windows of size `pool` that do not overlap.
+    pub fn new(pool: (usize, usize)) -> Self {
+        Self {
+            pool,
+            stride: pool,
+            format: Default::default(),
+            pooling: Pooling::new(false),
+        }
+    }
+
+    /// Use given `stride`.
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    /// Take and return images in given `format`.
+    pub fn with_format(mut self, format: DataFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Splits the images of given `input_shape` in windows, for `Layer::build`.
+    fn build_pooling(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+        self.pooling.build(
+            self.name(),
+            input_shape,
+            self.format,
+            self.pool,
+            self.stride,
+        )
+    }
+}
+
+pooling_layer!(AvgPool2D, "avg_pool2d");
+
+// * Global max pooling layer
+/// Global max pooling : reduces each channel of the input images to its maximum,
+/// returning one value per channel (of shape `[channels]`).
+#[derive(Clone, Debug)]
+pub struct GlobalMaxPooling2D {
+    pub format: DataFormat,
+    pooling: Pooling,
+}
+
+impl Default for GlobalMaxPooling2D {
+    fn default() -> Self {
+        Self {
+            format: Default::default(),
+            pooling: Pooling::new(true),
+        }
+    }
+}
+
+impl GlobalMaxPooling2D {
+    /// Returns a new `GlobalMaxPooling2D` layer.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Take images in given `format`.
+    pub fn with_format(mut self, format: DataFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Makes one window per channel of the images of given `input_shape`, for `Layer::build`.
+    fn build_pooling(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+        self.pooling
+            .build_global(self.name(), input_shape, self.format)
+    }
+}
+
+pooling_layer!(GlobalMaxPooling2D, "global_max_pool2d");
+
+// * Global average pooling layer
+/// Global average pooling : reduces each channel of the input images to its average,
+/// returning one value per channel (of shape `[channels]`).
+#[derive(Clone, Debug)]
+pub struct GlobalAveragePooling2D {
+    pub format: DataFormat,
+    pooling: Pooling,
+}
+
+impl Default for GlobalAveragePooling2D {
+    fn default() -> Self {
+        Self {
+            format: Default::default(),
+            pooling: Pooling::new(false),
+        }
+    }
+}
+
+impl GlobalAveragePooling2D {
+    /// Returns a new `GlobalAveragePooling2D` layer.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Take images in given `format`.
+    pub fn with_format(mut self, format: DataFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Makes one window per channel of the images of given `input_shape`, for `Layer::build`.
+    fn build_pooling(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+        self.pooling
+            .build_global(self.name(), input_shape, self.format)
+    }
+}
+
+pooling_layer!(GlobalAveragePooling2D, "global_avg_pool2d");
@@ -0,0 +1,120 @@
+use super::{shape_size, Layer};
+use crate::{Array2, Axis, Initializer, Phase, SpitzError};
+use ndarray_rand::rand::RngCore;
+use std::any::Any;
+
+// * Parametric relu layer
+/// Parametric relu : for each input `x`, returns `x` if `x > 0`, else `alpha * x`,
+/// with one `alpha` slope per input trained by the optimizer.\
+/// Usually added after a `Dense` layer with a `Linear` activation.
+///
+/// #### Derivatives :
+/// `∂f/∂x = 1 if x > 0, else alpha`\
+/// `∂f/∂alpha = 0 if x > 0, else x`
+#[derive(Clone, Debug)]
+pub struct PRelu {
+    /// Value of every slope once initialized.
+    pub initial_alpha: f64,
+    /// Trained slopes, one `(1, size)` row.
+    pub alpha: Array2<f64>,
+    shape: Vec<usize>,
+    alpha_grad: Array2<f64>,
+    /// Inputs of the last forward pass.
+    inputs: Array2<f64>,
+}
+
+impl Default for PRelu {
+    fn default() -> Self {
+        Self {
+            initial_alpha: 0.25,
+            alpha: Array2::zeros((1, 0)),
+            shape: Vec::new(),
+            alpha_grad: Array2::zeros((1, 0)),
+            inputs: Array2::zeros((0, 0)),
+        }
+    }
+}
+
+impl PRelu {
+    /// Returns a new `PRelu` layer, whose slopes start at `0.25`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Start the slopes at given `alpha`.
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        self.initial_alpha = alpha;
+        self
+    }
+}
+
+impl Layer for PRelu {
+    fn name(&self) -> &'static str {
+        "prelu"
+    }
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+        self.shape = input_shape.to_vec();
+        Ok(self.shape.clone())
+    }
+    fn input_shape(&self) -> Vec<usize> {
+        self.shape.clone()
+    }
+    fn output_shape(&self) -> Vec<usize> {
+        self.shape.clone()
+    }
+    fn init(
+        &mut self,
+        _initializer: Option<&Initializer>,
+        _rng: &mut dyn RngCore,
+    ) -> Result<(), SpitzError> {
+        self.alpha = Array2::from_elem((1, shape_size(&self.shape)), self.initial_alpha);
+        Ok(())
+    }
+    fn is_initialized(&self) -> bool {
+        self.alpha.shape() == [1, shape_size(&self.shape)]
+    }
+    fn forward(
+        &mut self,
+        inputs: &Array2<f64>,
+        _phase: Phase,
+        _rng: &mut dyn RngCore,
+    ) -> Array2<f64> {
+        self.inputs = inputs.clone();
+        self.infer(inputs)
+    }
+    fn infer(&self, inputs: &Array2<f64>) -> Array2<f64> {
+        let mut outputs = inputs.clone();
+        outputs.zip_mut_with(&self.alpha, |x, &alpha| {
+            if *x < 0. {
+                *x *= alpha
+            }
+        });
+        outputs
+    }
+    fn backward(&mut self, grad: &Array2<f64>) -> Array2<f64> {
+        // `1` where the input is negative, `0` elsewhere
+        let negative = self.inputs.mapv(|x| if x < 0. { 1. } else { 0. });
+        self.alpha_grad = (grad * &self.inputs * &negative)
+            .sum_axis(Axis(0))
+            .insert_axis(Axis(0));
+        grad * &(&negative * &self.alpha + negative.mapv(|n| 1. - n))
+    }
+    fn parameters(&self) -> Vec<&Array2<f64>> {
+        vec![&self.alpha]
+    }
+    fn parameters_mut(&mut self) -> Vec<&mut Array2<f64>> {
+        vec![&mut self.alpha]
+    }
+    fn gradients(&self) -> Vec<&Array2<f64>> {
+        vec![&self.alpha_grad]
+    }
+    fn box_clone(&self) -> Box<dyn Layer> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -97,7 +97,8 @@ let mut network = NNetwork::new();
 // import_train_datas : two ndarray's Array2,
 // one represents the training inputs : `x`,
 // the other one the training outputs : `y`.
-network.import_train_datas(x, y);
+network.import_train_datas(x, y)?;
+# Ok::<(), SpitzError>(())
 ```
 
 Generally, we will import testing datas too, or we can simply call `import_datas` that will divide our dataset into testing and training parts.
@@ -112,16 +113,26 @@ use ndarray::prelude::*;
 let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
 let y = &array![[0.], [1.], [1.], [0.]];
 let mut network = NNetwork::new();
-network.import_train_datas(x, y);
+network.import_train_datas(x, y)?;
 
 // we set the size of every layer, the last one will be the output layer.
 // we also need to define the activation function to use for each one (excepted the input layer).
 network.input_layer(2)
-    .add_layer(4, Activation::Relu)
-    .add_layer(5, Activation::Sigmoid)
-    .add_layer(1, Activation::Sigmoid);
+    .add_layer(4, Activation::Relu)?
+    .add_layer(5, Activation::Sigmoid)?
+    .add_layer(1, Activation::Sigmoid)?;
+# Ok::<(), SpitzError>(())
 ```
 
+If the input layer size is not set, it is inferred from the width of the training inputs during `init`.\
+Likewise, `output_layer(activation)` adds an output layer as wide as the training outputs.
+
+Available activations are `Relu`, `LeakyRelu(slope)`, `Elu(alpha)`, `Selu`, `Gelu`, `Swish`, `Mish`, `Softplus`,
+`Sigmoid`, `Tanh`, `Linear` and `Softmax`.\
+For a leaky relu whose slopes are trained, add a `PRelu` layer with `add(PRelu::new())`, after a `Linear` layer.
+Your own element-wise functions implement `ActivationFunction` and are used with `Activation::Custom(Arc::new(...))` ;
+to load a network using them, register them in an `ActivationRegistry` given to `NNetwork::load_with`.
+
 We can then set the network hyperparameters :
 
 ```rust
@@ -130,23 +141,31 @@ use ndarray::prelude::*;
 let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
 let y = &array![[0.], [1.], [1.], [0.]];
 let mut network = NNetwork::new();
-network.import_train_datas(x, y)
+network.import_train_datas(x, y)?
     .input_layer(2)
-    .add_layer(4, Activation::Relu)
-    .add_layer(5, Activation::Sigmoid)
-    .add_layer(1, Activation::Sigmoid);
+    .add_layer(4, Activation::Relu)?
+    .add_layer(5, Activation::Sigmoid)?
+    .add_layer(1, Activation::Sigmoid)?;
 
 // set hyperparameters
-network.set_epochs(35)
-    .set_batches(1)
+network.set_epochs(35)?
+    .set_batches(1)?
     .set_learning_rate(0.3);
+# Ok::<(), SpitzError>(())
 ```
 
 The defaults are :
 
 - learning rate = 0.03
 - number of epochs = 15
-- data per batch = 15
+- data per batch = 15 (the last batch of each epoch can be smaller, see `set_drop_last` to drop it)
+- training datas are shuffled at each epoch (see `set_shuffle` to keep their order)
+- loss = mean squared error (see `Loss` to change it with `set_loss`)
+- random seed = drawn from the system entropy, different on every run (see `set_seed` to make the training reproducible)
+- no weights regularization nor constraint (see `regularization` to add some with `set_regularizer` and `set_constraint`)
+- weights initializer = He for `Relu` layers, Glorot otherwise (see `Initializer` to change it with `set_initializer`, or per layer with `add_layer_with_initializer`)
+- optimizer = plain gradient descent (see `optimizers` to change it with `set_optimizer`)
+- learning rate schedule = constant (see `schedules` to change it with `set_learning_rate_schedule`)
 
 The last thing to do is to init the network.\
 Simply do, once everything is set and just before the training :
@@ -157,17 +176,18 @@ use ndarray::prelude::*;
 let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
 let y = &array![[0.], [1.], [1.], [0.]];
 let mut network = NNetwork::new();
-network.import_train_datas(x, y)
+network.import_train_datas(x, y)?
     .input_layer(2)
-    .add_layer(4, Activation::Relu)
-    .add_layer(5, Activation::Sigmoid)
-    .add_layer(1, Activation::Sigmoid)
-    .set_epochs(35)
-    .set_batches(1)
+    .add_layer(4, Activation::Relu)?
+    .add_layer(5, Activation::Sigmoid)?
+    .add_layer(1, Activation::Sigmoid)?
+    .set_epochs(35)?
+    .set_batches(1)?
     .set_learning_rate(0.3);
 
 // init the network
-network.init();
+network.init()?;
+# Ok::<(), SpitzError>(())
 ```
 
 ### Train the network
@@ -180,22 +200,86 @@ use ndarray::prelude::*;
 let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
 let y = &array![[0.], [1.], [1.], [0.]];
 let mut network = NNetwork::new();
-network.import_train_datas(x, y)
+network.import_train_datas(x, y)?
     .input_layer(2)
-    .add_layer(4, Activation::Relu)
-    .add_layer(5, Activation::Sigmoid)
-    .add_layer(1, Activation::Sigmoid)
-    .set_epochs(35)
-    .set_batches(1)
+    .add_layer(4, Activation::Relu)?
+    .add_layer(5, Activation::Sigmoid)?
+    .add_layer(1, Activation::Sigmoid)?
+    .set_epochs(35)?
+    .set_batches(1)?
     .set_learning_rate(0.3)
-    .init();
+    .init()?;
 
 // fit (train) the network
-network.fit();
+network.fit()?;
+# Ok::<(), SpitzError>(())
 ```
 
 After that, your network is ready and trained !
 
+#### History
+
+Each call to `fit` records the training loss, the testing loss (if testing datas were imported),
+the learning rate and the duration of every epoch in a `History`.\
+Get it with `get_history`, and export it with `to_csv` / `to_json` (or `save_csv` / `save_json`) to plot the learning curves.
+
+#### Errors
+
+Every call that can fail (importing datas that are not aligned, adding a layer before the input layer size is known,
+initializing a network whose layers do not fit the datas...) returns a `Result` with a `SpitzError` describing the problem,
+so that `spitz` never panics on a configuration mistake.
+
+#### Regularization
+
+To prevent overfitting, an L1, L2 or elastic-net penalty on the weights can be added to the training loss with `set_regularizer`,
+and a max-norm or unit-norm constraint applied after each update with `set_constraint`.\
+`set_layer_regularizer` and `set_layer_constraint` override them for a single layer.
+
+`add_dropout` randomly drops the outputs of the last added layer during the training,
+with a `Dropout::Standard` or, for self-normalizing networks, a `Dropout::Alpha`.\
+Dropouts are only applied during `fit` (in the `Phase::Training` phase), so `predict` and `evaluate` stay deterministic.
+
+#### Batch normalization
+
+`add_batch_norm` normalizes the outputs of the last added layer over each batch,
+which stabilizes the training of deep networks. Its scale and shift are learned with the weights,
+and running statistics replace the batch ones once trained.
+
+#### Layers
+
+Dense layers, dropouts and batch normalizations all implement the `Layer` trait.\
+To use a layer of your own, implement `Layer` for it (its `forward`, `infer` and `backward` passes,
+and the parameters it trains) and add it to the architecture with `add`; it is then trained like the others.
+Such layers cannot be saved.
+
+#### Convolutions
+
+Images are imported like any other datas, one flattened image per row.
+Set their shape with `input_shape(&[channels, height, width])`, then add `Conv2D` layers with `add` :
+`Conv2D::new(filters, (3, 3), Activation::Relu)`, configured with `with_stride`, `with_padding` and `with_dilation`.\
+For images flattened from NHWC tensors, use `input_shape(&[height, width, channels])`
+and `with_format(DataFormat::ChannelsLast)`. Dense layers added after a convolution flatten its outputs.
+
+`MaxPool2D` and `AvgPool2D` downsample the images by windows, while `GlobalMaxPooling2D` and `GlobalAveragePooling2D`
+reduce each channel to a single value ; every layer is sized from the output shape of the previous one.
+
+#### Gradient checking
+
+`gradient_check(x, y, eps)` compares the gradients computed by backpropagation with finite differences of the loss,
+and returns the relative error of every parameter of every layer : use it to verify a custom layer or activation.
+
+#### Early stopping
+
+To stop the training once it does not improve anymore, set an `EarlyStopping` with `set_early_stopping` :
+it monitors the training loss, the testing loss or a metric, with a `patience` and a `min_delta`,
+and can restore the weights of the best epoch. The number of epochs then becomes an upper bound.
+
+#### Callbacks
+
+To run your own code during the training (logging, checkpointing, alerting...), implement the `Callback` trait and add it with `add_callback`.\
+Its hooks (`on_train_begin`, `on_epoch_begin`, `on_batch_begin`, `on_batch_end`, `on_epoch_end` and `on_train_end`) can read the network,
+and can stop the training by returning `Control::Stop`.
+
 #### Logging
 
 If you want to have outputs during the training process (and before), you should use a logger.\
@@ -210,10 +294,9 @@ simple_logger::init_with_level(log::Level::Debug).unwrap();
 
 ### Predict with your network
 
-For the moment, predicting with the network is not as easy (but just need a decent implementation).\
-So, you can use the `feed_forward` method that the network uses during training.\
-
-Its output is the entire feed forwarded network, so you will need to manually get the last element of the network (as it is the output layer).
+Once trained, use `predict` to get the output layer of the network for some inputs.\
+For classification problems, `predict_classes` returns the index of the most likely class of each input,
+and `predict_proba` the probability of each class (or of the class `1`, for a single output).
 
 ```rust
 use spitz::*;
@@ -221,24 +304,57 @@ use ndarray::prelude::*;
 let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
 let y = &array![[0.], [1.], [1.], [0.]];
 let mut network = NNetwork::new();
-network.import_train_datas(x, y)
+network.import_train_datas(x, y)?
     .input_layer(2)
-    .add_layer(4, Activation::Relu)
-    .add_layer(5, Activation::Sigmoid)
-    .add_layer(1, Activation::Sigmoid)
-    .set_epochs(35)
-    .set_batches(1)
+    .add_layer(4, Activation::Relu)?
+    .add_layer(5, Activation::Sigmoid)?
+    .add_layer(1, Activation::Sigmoid)?
+    .set_epochs(35)?
+    .set_batches(1)?
     .set_learning_rate(0.3)
-    .init()
-    .fit();
+    .init()?
+    .fit()?;
 
 // predict
-let prediction = network.feed_forward(x)
-    .last()
-    .unwrap()
-    .clone();
+let prediction = network.predict(x)?;
+assert_eq!(prediction.shape(), &[4, 1]);
+# Ok::<(), SpitzError>(())
 ```
 
+### Evaluate your network
+
+`evaluate` runs the network on the imported testing datas, and returns its loss as well as the metrics set with `set_metrics`
+(accuracy, precision, recall, F1, MAE, RMSE, R² : see `Metric`).\
+`evaluate_on(x, y)` does the same on any datas, and `set_evaluate_each_epoch(true)` evaluates the network at the end of each epoch,
+recording the metrics in the `History`.
+
+### Save and load your network
+
+Once trained, the network can be saved to a file, and loaded back later :
+
+```rust
+use spitz::*;
+use ndarray::prelude::*;
+let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
+let y = &array![[0.], [1.], [1.], [0.]];
+let mut network = NNetwork::new();
+network.import_train_datas(x, y)?
+    .input_layer(2)
+    .add_layer(4, Activation::Relu)?
+    .add_layer(1, Activation::Sigmoid)?
+    .init()?
+    .fit()?;
+let path = std::env::temp_dir().join("xor.sptz");
+
+network.save(&path)?;
+
+let loaded = NNetwork::load(&path)?;
+# Ok::<(), SpitzError>(())
+```
+
+The file contains the architecture, the parameters and the hyperparameters of the network.\
+Its format is versioned : a file written by an incompatible version of `spitz` is refused with an error.
+
 ## Contributing
 
 Please feel *free* to contribute to that project, fork it, clone it, make it suffer, do whatever you want actually.\
@@ -252,8 +368,6 @@ Sincerely yours,\
 #![crate_type = "lib"]
 #![forbid(unsafe_code)]
 
-const WEIGHTS_INIT_MIN: f64 = -0.2;
-const WEIGHTS_INIT_MAX: f64 = 0.2;
 const DEFAULT_LN: f64 = 0.03;
 const DEFAULT_EPOCHS: usize = 15;
 const DEFAULT_BATCHES: usize = 15;
@@ -262,6 +376,7 @@ const DEFAULT_BATCHES: usize = 15;
 use ndarray::prelude::*;
 
 // ndarray_rand
+use ndarray_rand::rand::{rngs::StdRng, SeedableRng};
 use ndarray_rand::rand_distr::Uniform;
 use ndarray_rand::RandomExt;
 
@@ -269,12 +384,32 @@ use ndarray_rand::RandomExt;
 extern crate log;
 
 // Internal files
+pub mod callbacks;
+pub mod early_stopping;
+pub mod errors;
+pub mod history;
 mod interfaces;
+pub mod layers;
 pub mod maths;
 pub mod nnetwork;
+pub mod optimizers;
+pub mod regularization;
+pub mod schedules;
+pub mod serialization;
 mod types;
+pub use callbacks::{Callback, Control};
+pub use early_stopping::{EarlyStopping, Monitor};
+pub use errors::SpitzError;
+pub use history::History;
 pub use interfaces::{PrivateCalls, PublicCalls};
-pub use maths::Activation;
+pub use layers::{
+    AvgPool2D, BatchNorm, Conv2D, DataFormat, Dense, GlobalAveragePooling2D, GlobalMaxPooling2D,
+    Layer, MaxPool2D, PRelu,
+};
+pub use maths::{Activation, ActivationFunction, ActivationRegistry, Initializer, Loss, Metric};
+pub use optimizers::Optimizer;
+pub use regularization::{Constraint, Dropout, Regularizer};
+pub use schedules::LearningRateSchedule;
 pub use types::*;
 
 /// Perceptron constitued of multiple layers.
@@ -283,12 +418,27 @@ pub struct NNetwork {
     pub learning_rate: f64,
     pub epochs: usize,
     pub batches: usize,
+    pub shuffle: bool,
+    pub drop_last: bool,
+    pub loss: Loss,
+    pub initializer: Option<Initializer>,
+    pub regularizer: Option<Regularizer>,
+    pub constraint: Option<Constraint>,
+    pub metrics: Vec<Metric>,
+    pub evaluate_each_epoch: bool,
+    pub early_stopping: Option<EarlyStopping>,
     pub datas_raw: DatasRaw,
     pub datas: Datas,
     // Private; is used internally
-    weights: Weights,
     architecture: Architecture,
-    grads: Weights,
+    optimizer: Box<dyn Optimizer>,
+    schedule: Box<dyn LearningRateSchedule>,
+    callbacks: Vec<Box<dyn Callback>>,
+    rng: StdRng,
+    phase: Phase,
+    history: History,
     epoch: usize,
     batch: usize,
+    /// Set by `load`, so that the next `init` keeps the loaded parameters.
+    loaded: bool,
 }
@@ -1,4 +1,7 @@
-use ndarray::prelude::Array2;
+use ndarray::prelude::{Array2, Axis};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
 
 /// Utilitaries for activation functions.
 // TODO remove this mod if not used at all
@@ -8,8 +11,71 @@ mod utils {
     pub fn sig(x: f64) -> f64 {
         1. / (1. + (-x).exp())
     }
+
+    /// Returns `ln(1 + exp(x))`, without overflowing for large `x`.
+    #[inline]
+    pub fn softplus(x: f64) -> f64 {
+        x.max(0.) + (-x.abs()).exp().ln_1p()
+    }
+}
+
+// * Custom activations
+/// An element-wise activation function, to use in a network with `Activation::Custom`.
+pub trait ActivationFunction: fmt::Debug + Send + Sync {
+    /// Returns the name of the function, used to save networks and to find it back when loading them.\
+    /// It must differ from the names of the other activations.
+    fn name(&self) -> &str;
+    /// Applies the function to each element of `x`.
+    fn forward(&self, x: Array2<f64>) -> Array2<f64>;
+    /// Returns the derivative of the function at each element of `x`.
+    fn derivative(&self, x: Array2<f64>) -> Array2<f64>;
+}
+
+/// Custom activations are equal if they have the same name.
+impl PartialEq for dyn ActivationFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+/// Custom activations known when loading a network (see `NNetwork::load_with`).
+#[derive(Clone, Debug, Default)]
+pub struct ActivationRegistry {
+    functions: HashMap<String, Arc<dyn ActivationFunction>>,
+}
+
+impl ActivationRegistry {
+    /// Returns a new empty registry.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers `function` under its name, replacing any function registered with the same name.
+    pub fn register(&mut self, function: Arc<dyn ActivationFunction>) -> &mut Self {
+        self.functions.insert(function.name().to_string(), function);
+        self
+    }
+
+    /// Returns the activation called `name` with given `parameter` : a built-in one,
+    /// else a registered custom one, if it exists.
+    pub fn activation(&self, name: &str, parameter: f64) -> Option<super::Activation> {
+        super::Activation::from_name(name, parameter).or_else(|| {
+            self.functions
+                .get(name)
+                .map(|function| super::Activation::Custom(function.clone()))
+        })
+    }
 }
 
+/// Scale of the SELU activation, `λ`.
+pub const SELU_SCALE: f64 = 1.050_700_987_355_480_5;
+/// Negative saturation of the SELU activation, `α`.
+pub const SELU_ALPHA: f64 = 1.673_263_242_354_377_3;
+/// `sqrt(2 / π)`, used by the GELU approximation.
+const GELU_SCALE: f64 = 0.797_884_560_802_865_4;
+/// Cubic coefficient of the GELU approximation.
+const GELU_CUBIC: f64 = 0.044_715;
+
 pub fn relu(x: Array2<f64>, derivative: bool) -> Array2<f64> {
     if !derivative {
         x.mapv(|x| if x < 0. { 0. } else { x })
@@ -20,9 +86,9 @@ pub fn relu(x: Array2<f64>, derivative: bool) -> Array2<f64> {
 
 pub fn sigmoid(x: Array2<f64>, derivative: bool) -> Array2<f64> {
     if !derivative {
-        x.mapv(|x| utils::sig(x) * (1. - utils::sig(x)))
-    } else {
         x.mapv(utils::sig)
+    } else {
+        x.mapv(|x| utils::sig(x) * (1. - utils::sig(x)))
     }
 }
 
@@ -33,3 +99,104 @@ pub fn linear(x: Array2<f64>, derivative: bool) -> Array2<f64> {
         Array2::ones(x.raw_dim())
     }
 }
+
+pub fn tanh(x: Array2<f64>, derivative: bool) -> Array2<f64> {
+    if !derivative {
+        x.mapv(f64::tanh)
+    } else {
+        x.mapv(|x| 1. - x.tanh().powi(2))
+    }
+}
+
+/// Leaky relu, of given `slope` for negative inputs.
+pub fn leaky_relu(x: Array2<f64>, derivative: bool, slope: f64) -> Array2<f64> {
+    if !derivative {
+        x.mapv(|x| if x < 0. { slope * x } else { x })
+    } else {
+        x.mapv(|x| if x < 0. { slope } else { 1. })
+    }
+}
+
+/// Exponential linear unit, saturating to `-alpha` for negative inputs.
+pub fn elu(x: Array2<f64>, derivative: bool, alpha: f64) -> Array2<f64> {
+    if !derivative {
+        x.mapv(|x| if x < 0. { alpha * x.exp_m1() } else { x })
+    } else {
+        x.mapv(|x| if x < 0. { alpha * x.exp() } else { 1. })
+    }
+}
+
+/// Scaled exponential linear unit, the elu of `SELU_ALPHA` scaled by `SELU_SCALE`.
+pub fn selu(x: Array2<f64>, derivative: bool) -> Array2<f64> {
+    elu(x, derivative, SELU_ALPHA) * SELU_SCALE
+}
+
+/// Gaussian error linear unit, with its `tanh` approximation.
+pub fn gelu(x: Array2<f64>, derivative: bool) -> Array2<f64> {
+    if !derivative {
+        x.mapv(|x| 0.5 * x * (1. + (GELU_SCALE * (x + GELU_CUBIC * x.powi(3))).tanh()))
+    } else {
+        x.mapv(|x| {
+            let t = (GELU_SCALE * (x + GELU_CUBIC * x.powi(3))).tanh();
+            let dt = (1. - t * t) * GELU_SCALE * (1. + 3. * GELU_CUBIC * x * x);
+            0.5 * (1. + t) + 0.5 * x * dt
+        })
+    }
+}
+
+pub fn swish(x: Array2<f64>, derivative: bool) -> Array2<f64> {
+    if !derivative {
+        x.mapv(|x| x * utils::sig(x))
+    } else {
+        x.mapv(|x| {
+            let s = utils::sig(x);
+            s + x * s * (1. - s)
+        })
+    }
+}
+
+pub fn mish(x: Array2<f64>, derivative: bool) -> Array2<f64> {
+    if !derivative {
+        x.mapv(|x| x * utils::softplus(x).tanh())
+    } else {
+        x.mapv(|x| {
+            let t = utils::softplus(x).tanh();
+            t + x * (1. - t * t) * utils::sig(x)
+        })
+    }
+}
+
+pub fn softplus(x: Array2<f64>, derivative: bool) -> Array2<f64> {
+    if !derivative {
+        x.mapv(utils::softplus)
+    } else {
+        x.mapv(utils::sig)
+    }
+}
+
+/// Row-wise softmax : each row of the output sums to `1`.\
+/// As the softmax is not an element-wise function, its `derivative` only returns
+/// the diagonal of its jacobian, `s * (1 - s)` ; use `softmax_backward` to backpropagate through it.
+pub fn softmax(x: Array2<f64>, derivative: bool) -> Array2<f64> {
+    let mut s = x;
+    for mut row in s.genrows_mut() {
+        // Shifting by the maximum prevents `exp` from overflowing
+        let max = row.fold(f64::NEG_INFINITY, |m, &v| m.max(v));
+        row.mapv_inplace(|v| (v - max).exp());
+        let sum = row.sum();
+        row.mapv_inplace(|v| v / sum);
+    }
+    if !derivative {
+        s
+    } else {
+        s.mapv(|s| s * (1. - s))
+    }
+}
+
+/// Backpropagates `grad` (the derivative of the loss with respect to the softmax output `s`)
+/// through the softmax jacobian, row by row :\
+/// `∂L/∂x = s * (grad - sum(grad * s))`
+pub fn softmax_backward(s: &Array2<f64>, grad: &Array2<f64>) -> Array2<f64> {
+    let dot = (grad * s).sum_axis(Axis(1)).insert_axis(Axis(1));
+    s * &(grad - &dot)
+}
@@ -0,0 +1,160 @@
+use crate::{Array, Array2, RandomExt, SpitzError, Uniform};
+use ndarray_rand::rand::Rng;
+use ndarray_rand::rand_distr::Normal;
+
+/// Returns a `(fan_in, fan_out)` matrix drawn uniformly between `-limit` and `limit`.
+fn symmetric_uniform<R: Rng + ?Sized>(
+    fan_in: usize,
+    fan_out: usize,
+    limit: f64,
+    rng: &mut R,
+) -> Result<Array2<f64>, SpitzError> {
+    uniform(fan_in, fan_out, -limit, limit, rng)
+}
+
+/// Returns a `(fan_in, fan_out)` matrix drawn from a centered normal distribution of deviation `std`.
+fn centered_normal<R: Rng + ?Sized>(
+    fan_in: usize,
+    fan_out: usize,
+    std: f64,
+    rng: &mut R,
+) -> Result<Array2<f64>, SpitzError> {
+    if !std.is_finite() {
+        return Err(SpitzError::InvalidInitializer(format!(
+            "deviation {} is not finite",
+            std
+        )));
+    }
+    let normal = Normal::new(0., std)
+        .map_err(|e| SpitzError::InvalidInitializer(format!("deviation {} : {:?}", std, e)))?;
+    Ok(Array::random_using((fan_in, fan_out), normal, rng))
+}
+
+/// Returns a `(fan_in, fan_out)` matrix drawn uniformly between `min` and `max`.
+///
+/// ### Errors
+/// Returns `SpitzError::InvalidInitializer` if the bounds are not finite, or if `min > max`.
+pub fn uniform<R: Rng + ?Sized>(
+    fan_in: usize,
+    fan_out: usize,
+    min: f64,
+    max: f64,
+    rng: &mut R,
+) -> Result<Array2<f64>, SpitzError> {
+    if !(min.is_finite() && max.is_finite() && min <= max) {
+        return Err(SpitzError::InvalidInitializer(format!(
+            "bounds ({}, {}) are not finite and ordered",
+            min, max
+        )));
+    }
+    Ok(Array::random_using(
+        (fan_in, fan_out),
+        Uniform::new_inclusive(min, max),
+        rng,
+    ))
+}
+
+/// Glorot uniform : `limit = sqrt(6 / (fan_in + fan_out))`.
+pub fn glorot_uniform<R: Rng + ?Sized>(
+    fan_in: usize,
+    fan_out: usize,
+    rng: &mut R,
+) -> Result<Array2<f64>, SpitzError> {
+    symmetric_uniform(
+        fan_in,
+        fan_out,
+        (6. / (fan_in + fan_out) as f64).sqrt(),
+        rng,
+    )
+}
+
+/// Glorot normal : `std = sqrt(2 / (fan_in + fan_out))`.
+pub fn glorot_normal<R: Rng + ?Sized>(
+    fan_in: usize,
+    fan_out: usize,
+    rng: &mut R,
+) -> Result<Array2<f64>, SpitzError> {
+    centered_normal(
+        fan_in,
+        fan_out,
+        (2. / (fan_in + fan_out) as f64).sqrt(),
+        rng,
+    )
+}
+
+/// He uniform : `limit = sqrt(6 / fan_in)`.
+pub fn he_uniform<R: Rng + ?Sized>(
+    fan_in: usize,
+    fan_out: usize,
+    rng: &mut R,
+) -> Result<Array2<f64>, SpitzError> {
+    symmetric_uniform(fan_in, fan_out, (6. / fan_in as f64).sqrt(), rng)
+}
+
+/// He normal : `std = sqrt(2 / fan_in)`.
+pub fn he_normal<R: Rng + ?Sized>(
+    fan_in: usize,
+    fan_out: usize,
+    rng: &mut R,
+) -> Result<Array2<f64>, SpitzError> {
+    centered_normal(fan_in, fan_out, (2. / fan_in as f64).sqrt(), rng)
+}
+
+/// LeCun uniform : `limit = sqrt(3 / fan_in)`.
+pub fn lecun_uniform<R: Rng + ?Sized>(
+    fan_in: usize,
+    fan_out: usize,
+    rng: &mut R,
+) -> Result<Array2<f64>, SpitzError> {
+    symmetric_uniform(fan_in, fan_out, (3. / fan_in as f64).sqrt(), rng)
+}
+
+/// LeCun normal : `std = sqrt(1 / fan_in)`.
+pub fn lecun_normal<R: Rng + ?Sized>(
+    fan_in: usize,
+    fan_out: usize,
+    rng: &mut R,
+) -> Result<Array2<f64>, SpitzError> {
+    centered_normal(fan_in, fan_out, (1. / fan_in as f64).sqrt(), rng)
+}
+
+/// Returns a `(fan_in, fan_out)` matrix whose rows or columns (the smallest number of them)
+/// are orthonormal, multiplied by `gain`.\
+/// Made by orthonormalizing a normal random matrix with the Gram-Schmidt process.
+///
+/// ### Errors
+/// Returns `SpitzError::InvalidInitializer` if `gain` is not finite.
+pub fn orthogonal<R: Rng + ?Sized>(
+    fan_in: usize,
+    fan_out: usize,
+    gain: f64,
+    rng: &mut R,
+) -> Result<Array2<f64>, SpitzError> {
+    // Orthonormalize the columns of a tall matrix, transposed back if needed
+    let transposed = fan_in < fan_out;
+    let (rows, cols) = if transposed {
+        (fan_out, fan_in)
+    } else {
+        (fan_in, fan_out)
+    };
+    if !gain.is_finite() {
+        return Err(SpitzError::InvalidInitializer(format!(
+            "gain {} is not finite",
+            gain
+        )));
+    }
+    let mut q = centered_normal(rows, cols, 1., rng)?;
+
+    for j in 0..cols {
+        for k in 0..j {
+            let projection = q.column(j).dot(&q.column(k));
+            let previous = q.column(k).to_owned();
+            q.column_mut(j).scaled_add(-projection, &previous);
+        }
+        let norm = q.column(j).dot(&q.column(j)).sqrt();
+        q.column_mut(j).mapv_inplace(|v| v / norm);
+    }
+
+    q.mapv_inplace(|v| v * gain);
+    Ok(if transposed { q.reversed_axes() } else { q })
+}
@@ -0,0 +1,88 @@
+use ndarray::prelude::Array2;
+
+/// Lower bound used to clip predictions before taking their logarithm.
+const EPSILON: f64 = 1e-12;
+
+/// Returns the number of samples (rows) of `y`, as a float.
+#[inline]
+fn samples(y: &Array2<f64>) -> f64 {
+    y.nrows().max(1) as f64
+}
+
+pub fn mse(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    (y_pred - y).mapv(|e| e.powi(2)).mean().unwrap_or(0.)
+}
+
+pub fn mse_derivative(y_pred: &Array2<f64>, y: &Array2<f64>) -> Array2<f64> {
+    let n = y.len().max(1) as f64;
+    (y_pred - y).mapv(|e| 2. * e / n)
+}
+
+pub fn mae(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    (y_pred - y).mapv(f64::abs).mean().unwrap_or(0.)
+}
+
+pub fn mae_derivative(y_pred: &Array2<f64>, y: &Array2<f64>) -> Array2<f64> {
+    let n = y.len().max(1) as f64;
+    (y_pred - y).mapv(|e| {
+        if e > 0. {
+            1. / n
+        } else if e < 0. {
+            -1. / n
+        } else {
+            0.
+        }
+    })
+}
+
+pub fn huber(y_pred: &Array2<f64>, y: &Array2<f64>, delta: f64) -> f64 {
+    (y_pred - y)
+        .mapv(|e| {
+            if e.abs() <= delta {
+                0.5 * e.powi(2)
+            } else {
+                delta * (e.abs() - 0.5 * delta)
+            }
+        })
+        .mean()
+        .unwrap_or(0.)
+}
+
+pub fn huber_derivative(y_pred: &Array2<f64>, y: &Array2<f64>, delta: f64) -> Array2<f64> {
+    let n = y.len().max(1) as f64;
+    (y_pred - y).mapv(|e| e.clamp(-delta, delta) / n)
+}
+
+pub fn binary_cross_entropy(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    let mut total = 0.;
+    for (&p, &t) in y_pred.iter().zip(y) {
+        let p = p.clamp(EPSILON, 1. - EPSILON);
+        total -= t * p.ln() + (1. - t) * (1. - p).ln();
+    }
+    total / y.len().max(1) as f64
+}
+
+pub fn binary_cross_entropy_derivative(y_pred: &Array2<f64>, y: &Array2<f64>) -> Array2<f64> {
+    let n = y.len().max(1) as f64;
+    let mut grad = y_pred.clone();
+    grad.zip_mut_with(y, |p, &t| {
+        let q = p.clamp(EPSILON, 1. - EPSILON);
+        *p = (q - t) / (q * (1. - q)) / n;
+    });
+    grad
+}
+
+pub fn categorical_cross_entropy(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    let mut total = 0.;
+    for (&p, &t) in y_pred.iter().zip(y) {
+        total -= t * p.max(EPSILON).ln();
+    }
+    total / samples(y)
+}
+
+pub fn categorical_cross_entropy_derivative(y_pred: &Array2<f64>, y: &Array2<f64>) -> Array2<f64> {
+    let n = samples(y);
+    let mut grad = y_pred.clone();
+    grad.zip_mut_with(y, |p, &t| *p = -t / p.max(EPSILON) / n);
+    grad
+}
@@ -0,0 +1,116 @@
+use ndarray::prelude::{Array2, Axis};
+
+/// Returns the class of each row of `y` : the index of its highest value,
+/// or for a single column, `1` if the value is at least `0.5` and `0` otherwise.
+fn classes(y: &Array2<f64>) -> Vec<usize> {
+    y.genrows()
+        .into_iter()
+        .map(|row| {
+            if row.len() == 1 {
+                (row[0] >= 0.5) as usize
+            } else {
+                row.iter()
+                    .enumerate()
+                    .fold(0, |best, (id, &v)| if v > row[best] { id } else { best })
+            }
+        })
+        .collect()
+}
+
+/// Returns, for each class, the number of true positives, of predicted and of actual samples.\
+/// A single column is a binary problem, where only the positive class `1` is kept.
+fn confusion(y_pred: &Array2<f64>, y: &Array2<f64>) -> Vec<(f64, f64, f64)> {
+    let (predicted, actual) = (classes(y_pred), classes(y));
+    let counts = |class: usize| {
+        predicted
+            .iter()
+            .zip(&actual)
+            .fold((0., 0., 0.), |(tp, p, a), (&pred, &act)| {
+                (
+                    tp + (pred == class && act == class) as usize as f64,
+                    p + (pred == class) as usize as f64,
+                    a + (act == class) as usize as f64,
+                )
+            })
+    };
+    if y.ncols() == 1 {
+        vec![counts(1)]
+    } else {
+        (0..y.ncols()).map(counts).collect()
+    }
+}
+
+/// Returns `a / b`, or `0` if `b` is `0`.
+#[inline]
+fn ratio(a: f64, b: f64) -> f64 {
+    if b == 0. {
+        0.
+    } else {
+        a / b
+    }
+}
+
+/// Returns the mean of `values`, or `0` if there is none.
+#[inline]
+fn mean(values: &[f64]) -> f64 {
+    ratio(values.iter().sum(), values.len() as f64)
+}
+
+pub fn accuracy(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    let (predicted, actual) = (classes(y_pred), classes(y));
+    let correct = predicted
+        .iter()
+        .zip(&actual)
+        .filter(|(p, a)| p == a)
+        .count();
+    ratio(correct as f64, actual.len() as f64)
+}
+
+pub fn precision(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    let precisions: Vec<f64> = confusion(y_pred, y)
+        .iter()
+        .map(|&(tp, predicted, _)| ratio(tp, predicted))
+        .collect();
+    mean(&precisions)
+}
+
+pub fn recall(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    let recalls: Vec<f64> = confusion(y_pred, y)
+        .iter()
+        .map(|&(tp, _, actual)| ratio(tp, actual))
+        .collect();
+    mean(&recalls)
+}
+
+pub fn f1(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    let scores: Vec<f64> = confusion(y_pred, y)
+        .iter()
+        .map(|&(tp, predicted, actual)| {
+            let (p, r) = (ratio(tp, predicted), ratio(tp, actual));
+            ratio(2. * p * r, p + r)
+        })
+        .collect();
+    mean(&scores)
+}
+
+pub fn mae(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    (y_pred - y).mapv(f64::abs).mean().unwrap_or(0.)
+}
+
+pub fn rmse(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    (y_pred - y).mapv(|e| e.powi(2)).mean().unwrap_or(0.).sqrt()
+}
+
+pub fn r2(y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+    let residuals = (y_pred - y).mapv(|e| e.powi(2)).sum();
+    let total = match y.mean_axis(Axis(0)) {
+        Some(mean) => (y - &mean).mapv(|e| e.powi(2)).sum(),
+        None => 0.,
+    };
+    if total == 0. {
+        // Constant targets : only a perfect prediction explains them
+        (residuals == 0.) as usize as f64
+    } else {
+        1. - residuals / total
+    }
+}
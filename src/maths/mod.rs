@@ -1,9 +1,15 @@
 pub mod activations;
-use crate::Array2;
+pub mod initializers;
+pub mod losses;
+pub mod metrics;
+use crate::{Array2, SpitzError};
+pub use activations::{ActivationFunction, ActivationRegistry};
+use ndarray_rand::rand::Rng;
+use std::sync::Arc;
 
 pub type TransfertFunction = fn(Array2<f64>, bool) -> Array2<f64>;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 /// List the different implemented transfert function to use.
 pub enum Activation {
     /// ### Relu transfert function :
@@ -33,13 +39,407 @@ pub enum Activation {
     /// #### Derivative :
     /// `∂f/∂x = 1`
     Linear,
+    /// ### Softmax transfert function :
+    /// For each row `x` of the layer, returns `exp(x) / sum(exp(x))`.\
+    /// Used on the output layer for multi-class problems, generally with `Loss::CategoricalCrossEntropy`.
+    ///
+    /// #### Mathematically :
+    /// `f(x)_i = exp(x_i) / sum_j(exp(x_j))`
+    /// #### Derivative :
+    /// `∂f_i/∂x_j = f(x)_i * (δ_ij - f(x)_j)`
+    Softmax,
+    /// ### Tanh transfert function :
+    /// For each `x` element, returns the hyperbolic tangent of `x`.\
+    /// Centered alternative to `Sigmoid`, outputs in `(-1, 1)`.
+    ///
+    /// #### Mathematically :
+    /// `f(x) = tanh(x)`
+    /// #### Derivative :
+    /// `∂f/∂x = 1 - tanh(x)²`
+    Tanh,
+    /// ### Leaky relu transfert function :
+    /// For each `x` element, returns `x` if `x > 0`, else `slope * x` for the given `slope` (usually `0.01`).\
+    /// Avoids the dead neurons of `Relu`.
+    ///
+    /// #### Mathematically :
+    /// `f(x) = max(x, slope * x)`
+    /// #### Derivative :
+    /// `∂f/∂x = 1 if x > 0, else slope`
+    LeakyRelu(f64),
+    /// ### Elu transfert function :
+    /// For each `x` element, returns `x` if `x > 0`, else `alpha * (exp(x) - 1)` for the given `alpha` (usually `1.0`).
+    ///
+    /// #### Mathematically :
+    /// `f(x) = x if x > 0, else alpha * (exp(x) - 1)`
+    /// #### Derivative :
+    /// `∂f/∂x = 1 if x > 0, else alpha * exp(x)`
+    Elu(f64),
+    /// ### Selu transfert function :
+    /// Scaled `Elu`, with `λ ≈ 1.0507` and `α ≈ 1.6733`.\
+    /// Self-normalizing with `Initializer::LecunNormal` weights, to use with `Dropout::Alpha`.
+    ///
+    /// #### Mathematically :
+    /// `f(x) = λ * x if x > 0, else λ * α * (exp(x) - 1)`
+    /// #### Derivative :
+    /// `∂f/∂x = λ if x > 0, else λ * α * exp(x)`
+    Selu,
+    /// ### Gelu transfert function :
+    /// For each `x` element, returns `x * Φ(x)`, where `Φ` is the standard normal cumulative distribution,
+    /// computed with its usual `tanh` approximation.\
+    /// Used by most transformers.
+    ///
+    /// #### Mathematically :
+    /// `f(x) = 0.5 * x * (1 + tanh( sqrt(2 / π) * (x + 0.044715 * x³) ))`
+    /// #### Derivative :
+    /// `∂f/∂x = 0.5 * (1 + t) + 0.5 * x * (1 - t²) * sqrt(2 / π) * (1 + 3 * 0.044715 * x²)`,
+    /// with `t` the `tanh` term above
+    Gelu,
+    /// ### Swish transfert function :
+    /// For each `x` element, returns `x * sigmoid(x)` (also called SiLU).
+    ///
+    /// #### Mathematically :
+    /// `f(x) = x / (1 + exp( -x ))`
+    /// #### Derivative :
+    /// `∂f/∂x = σ(x) + x * σ(x) * (1 - σ(x))`, with `σ` the sigmoid
+    Swish,
+    /// ### Mish transfert function :
+    /// For each `x` element, returns `x * tanh(softplus(x))`.
+    ///
+    /// #### Mathematically :
+    /// `f(x) = x * tanh( ln(1 + exp(x)) )`
+    /// #### Derivative :
+    /// `∂f/∂x = tanh(sp(x)) + x * (1 - tanh(sp(x))²) * σ(x)`, with `sp` the softplus and `σ` the sigmoid
+    Mish,
+    /// ### Softplus transfert function :
+    /// For each `x` element, returns `ln(1 + exp(x))`, a smooth `Relu`.
+    ///
+    /// #### Mathematically :
+    /// `f(x) = ln(1 + exp(x))`
+    /// #### Derivative :
+    /// `∂f/∂x = 1 / (1 + exp( -x ))`
+    Softplus,
+    /// ### Custom transfert function :
+    /// User-defined element-wise function, implementing `ActivationFunction`.\
+    /// To load a network using it, register it in the `ActivationRegistry` given to `NNetwork::load_with`.
+    Custom(Arc<dyn ActivationFunction>),
 }
 impl Activation {
-    pub fn match_activation(act_type: Activation) -> TransfertFunction {
+    /// Returns the transfert function of `act_type`, or `None` if it has a parameter or is custom :
+    /// these are only applied through `compute`.
+    pub fn match_activation(act_type: Activation) -> Option<TransfertFunction> {
         match act_type {
-            Activation::Relu => activations::relu,
-            Activation::Sigmoid => activations::sigmoid,
-            Activation::Linear => activations::linear,
+            Activation::Relu => Some(activations::relu),
+            Activation::Sigmoid => Some(activations::sigmoid),
+            Activation::Linear => Some(activations::linear),
+            Activation::Softmax => Some(activations::softmax),
+            Activation::Tanh => Some(activations::tanh),
+            Activation::Selu => Some(activations::selu),
+            Activation::Gelu => Some(activations::gelu),
+            Activation::Swish => Some(activations::swish),
+            Activation::Mish => Some(activations::mish),
+            Activation::Softplus => Some(activations::softplus),
+            Activation::LeakyRelu(_) | Activation::Elu(_) | Activation::Custom(_) => None,
+        }
+    }
+
+    /// Returns the name of the transfert function and its parameter (`0` if it has none), used to save networks.
+    pub fn name(&self) -> (&str, f64) {
+        match *self {
+            Activation::Relu => ("relu", 0.),
+            Activation::Sigmoid => ("sigmoid", 0.),
+            Activation::Linear => ("linear", 0.),
+            Activation::Softmax => ("softmax", 0.),
+            Activation::Tanh => ("tanh", 0.),
+            Activation::LeakyRelu(slope) => ("leaky_relu", slope),
+            Activation::Elu(alpha) => ("elu", alpha),
+            Activation::Selu => ("selu", 0.),
+            Activation::Gelu => ("gelu", 0.),
+            Activation::Swish => ("swish", 0.),
+            Activation::Mish => ("mish", 0.),
+            Activation::Softplus => ("softplus", 0.),
+            Activation::Custom(ref function) => (function.name(), 0.),
+        }
+    }
+
+    /// Returns the built-in transfert function called `name` with given `parameter`, if it exists.
+    pub fn from_name(name: &str, parameter: f64) -> Option<Activation> {
+        match name {
+            "relu" => Some(Activation::Relu),
+            "sigmoid" => Some(Activation::Sigmoid),
+            "linear" => Some(Activation::Linear),
+            "softmax" => Some(Activation::Softmax),
+            "tanh" => Some(Activation::Tanh),
+            "leaky_relu" => Some(Activation::LeakyRelu(parameter)),
+            "elu" => Some(Activation::Elu(parameter)),
+            "selu" => Some(Activation::Selu),
+            "gelu" => Some(Activation::Gelu),
+            "swish" => Some(Activation::Swish),
+            "mish" => Some(Activation::Mish),
+            "softplus" => Some(Activation::Softplus),
+            _ => None,
+        }
+    }
+
+    /// Applies the transfert function (or its `derivative`) to `x`.
+    pub fn compute(&self, x: Array2<f64>, derivative: bool) -> Array2<f64> {
+        match self {
+            Activation::Relu => activations::relu(x, derivative),
+            Activation::Sigmoid => activations::sigmoid(x, derivative),
+            Activation::Linear => activations::linear(x, derivative),
+            Activation::Softmax => activations::softmax(x, derivative),
+            Activation::Tanh => activations::tanh(x, derivative),
+            Activation::LeakyRelu(slope) => activations::leaky_relu(x, derivative, *slope),
+            Activation::Elu(alpha) => activations::elu(x, derivative, *alpha),
+            Activation::Selu => activations::selu(x, derivative),
+            Activation::Gelu => activations::gelu(x, derivative),
+            Activation::Swish => activations::swish(x, derivative),
+            Activation::Mish => activations::mish(x, derivative),
+            Activation::Softplus => activations::softplus(x, derivative),
+            Activation::Custom(function) if derivative => function.derivative(x),
+            Activation::Custom(function) => function.forward(x),
+        }
+    }
+
+    /// Backpropagates `grad`, the error with respect to the layer output `y = f(z)`,
+    /// to the error with respect to the layer weighted average `z`.\
+    /// Element-wise activations multiply by their derivative at `z`,
+    /// whereas `Softmax` goes through its whole jacobian, computed from `y`.
+    pub fn backward(&self, z: &Array2<f64>, y: &Array2<f64>, grad: Array2<f64>) -> Array2<f64> {
+        match self {
+            Activation::Softmax => activations::softmax_backward(y, &grad),
+            _ => grad * self.compute(z.clone(), true),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Default)]
+/// List the different implemented loss functions to use.\
+/// Each one provides the loss value and its derivative with respect to the network output.
+pub enum Loss {
+    /// ### Mean squared error :
+    /// Mean of the squared differences, the default loss.\
+    /// Mostly used for regression problems.
+    ///
+    /// #### Mathematically :
+    /// `L = mean( (ŷ - y)² )`
+    /// #### Derivative :
+    /// `∂L/∂ŷ = 2 * (ŷ - y) / N`
+    #[default]
+    MeanSquaredError,
+    /// ### Mean absolute error :
+    /// Mean of the absolute differences, less sensitive to outliers than `MeanSquaredError`.
+    ///
+    /// #### Mathematically :
+    /// `L = mean( |ŷ - y| )`
+    /// #### Derivative :
+    /// `∂L/∂ŷ = sign(ŷ - y) / N`
+    MeanAbsoluteError,
+    /// ### Huber loss :
+    /// Quadratic for errors smaller than `delta`, linear above.\
+    /// The parameter is `delta`, usually `1.0`.
+    ///
+    /// #### Mathematically :
+    /// `L = mean( 0.5 * e² if |e| <= delta, else delta * (|e| - 0.5 * delta) )` with `e = ŷ - y`
+    /// #### Derivative :
+    /// `∂L/∂ŷ = clamp(e, -delta, delta) / N`
+    Huber(f64),
+    /// ### Binary cross-entropy :
+    /// To use with a `Sigmoid` output, where each output is an independent probability.
+    ///
+    /// #### Mathematically :
+    /// `L = -mean( y * ln(ŷ) + (1 - y) * ln(1 - ŷ) )`
+    /// #### Derivative :
+    /// `∂L/∂ŷ = (ŷ - y) / (ŷ * (1 - ŷ)) / N`
+    BinaryCrossEntropy,
+    /// ### Categorical cross-entropy :
+    /// To use with one-hot encoded labels, generally with a `Softmax` output.\
+    /// Averaged over the samples, not over the classes.
+    ///
+    /// #### Mathematically :
+    /// `L = -sum( y * ln(ŷ) ) / samples`
+    /// #### Derivative :
+    /// `∂L/∂ŷ = -y / ŷ / samples`
+    CategoricalCrossEntropy,
+}
+impl Loss {
+    /// Returns the name of the loss and its parameter (`0` if it has none), used to save networks.
+    pub fn name(&self) -> (&'static str, f64) {
+        match *self {
+            Loss::MeanSquaredError => ("mse", 0.),
+            Loss::MeanAbsoluteError => ("mae", 0.),
+            Loss::Huber(delta) => ("huber", delta),
+            Loss::BinaryCrossEntropy => ("binary_cross_entropy", 0.),
+            Loss::CategoricalCrossEntropy => ("categorical_cross_entropy", 0.),
+        }
+    }
+
+    /// Returns the loss called `name` with given `parameter`, if it exists.
+    pub fn from_name(name: &str, parameter: f64) -> Option<Loss> {
+        match name {
+            "mse" => Some(Loss::MeanSquaredError),
+            "mae" => Some(Loss::MeanAbsoluteError),
+            "huber" => Some(Loss::Huber(parameter)),
+            "binary_cross_entropy" => Some(Loss::BinaryCrossEntropy),
+            "categorical_cross_entropy" => Some(Loss::CategoricalCrossEntropy),
+            _ => None,
+        }
+    }
+
+    /// Returns the loss value of the prediction `y_pred` against the expected `y`.
+    pub fn loss(&self, y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+        match *self {
+            Loss::MeanSquaredError => losses::mse(y_pred, y),
+            Loss::MeanAbsoluteError => losses::mae(y_pred, y),
+            Loss::Huber(delta) => losses::huber(y_pred, y, delta),
+            Loss::BinaryCrossEntropy => losses::binary_cross_entropy(y_pred, y),
+            Loss::CategoricalCrossEntropy => losses::categorical_cross_entropy(y_pred, y),
+        }
+    }
+
+    /// Returns the derivative of the loss with respect to each element of `y_pred`.
+    pub fn derivative(&self, y_pred: &Array2<f64>, y: &Array2<f64>) -> Array2<f64> {
+        match *self {
+            Loss::MeanSquaredError => losses::mse_derivative(y_pred, y),
+            Loss::MeanAbsoluteError => losses::mae_derivative(y_pred, y),
+            Loss::Huber(delta) => losses::huber_derivative(y_pred, y, delta),
+            Loss::BinaryCrossEntropy => losses::binary_cross_entropy_derivative(y_pred, y),
+            Loss::CategoricalCrossEntropy => {
+                losses::categorical_cross_entropy_derivative(y_pred, y)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// List the different implemented weights initializations to use.\
+/// `fan_in` is the input size of the layer, `fan_out` its size.\
+/// Biases are always initialized at `0`.
+pub enum Initializer {
+    /// ### Uniform initialization :
+    /// Draws weights uniformly between the two given bounds `min` and `max`.
+    Uniform(f64, f64),
+    /// ### Glorot (or Xavier) uniform initialization :
+    /// Draws weights uniformly in `[-l, l]` with `l = sqrt(6 / (fan_in + fan_out))`.\
+    /// Default for `Sigmoid`, `Linear` and `Softmax` layers.
+    GlorotUniform,
+    /// ### Glorot (or Xavier) normal initialization :
+    /// Draws weights from a centered normal distribution of deviation `sqrt(2 / (fan_in + fan_out))`.
+    GlorotNormal,
+    /// ### He (or Kaiming) uniform initialization :
+    /// Draws weights uniformly in `[-l, l]` with `l = sqrt(6 / fan_in)`.
+    HeUniform,
+    /// ### He (or Kaiming) normal initialization :
+    /// Draws weights from a centered normal distribution of deviation `sqrt(2 / fan_in)`.\
+    /// Default for `Relu` layers.
+    HeNormal,
+    /// ### LeCun uniform initialization :
+    /// Draws weights uniformly in `[-l, l]` with `l = sqrt(3 / fan_in)`.
+    LecunUniform,
+    /// ### LeCun normal initialization :
+    /// Draws weights from a centered normal distribution of deviation `sqrt(1 / fan_in)`.
+    LecunNormal,
+    /// ### Orthogonal initialization :
+    /// Draws a random matrix with orthonormal rows or columns, multiplied by the given `gain` (usually `1.0`).
+    Orthogonal(f64),
+}
+impl Initializer {
+    /// Returns the initializer that best suits layers using given `activation`.
+    pub fn for_activation(activation: &Activation) -> Initializer {
+        match activation {
+            Activation::Relu
+            | Activation::LeakyRelu(_)
+            | Activation::Elu(_)
+            | Activation::Gelu
+            | Activation::Swish
+            | Activation::Mish
+            | Activation::Softplus => Initializer::HeNormal,
+            Activation::Selu => Initializer::LecunNormal,
+            Activation::Sigmoid
+            | Activation::Linear
+            | Activation::Softmax
+            | Activation::Tanh
+            | Activation::Custom(_) => Initializer::GlorotUniform,
+        }
+    }
+
+    /// Returns a new `(fan_in, fan_out)` weights matrix, drawn with given `rng`.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::InvalidInitializer` if the parameters of the initializer are invalid,
+    /// e.g. `Uniform` bounds that are not finite and ordered.
+    pub fn weights<R: Rng + ?Sized>(
+        &self,
+        fan_in: usize,
+        fan_out: usize,
+        rng: &mut R,
+    ) -> Result<Array2<f64>, SpitzError> {
+        match *self {
+            Initializer::Uniform(min, max) => initializers::uniform(fan_in, fan_out, min, max, rng),
+            Initializer::GlorotUniform => initializers::glorot_uniform(fan_in, fan_out, rng),
+            Initializer::GlorotNormal => initializers::glorot_normal(fan_in, fan_out, rng),
+            Initializer::HeUniform => initializers::he_uniform(fan_in, fan_out, rng),
+            Initializer::HeNormal => initializers::he_normal(fan_in, fan_out, rng),
+            Initializer::LecunUniform => initializers::lecun_uniform(fan_in, fan_out, rng),
+            Initializer::LecunNormal => initializers::lecun_normal(fan_in, fan_out, rng),
+            Initializer::Orthogonal(gain) => initializers::orthogonal(fan_in, fan_out, gain, rng),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// List the different implemented metrics, used to evaluate the network.\
+/// Classification metrics take the class of each sample as the index of its highest output,
+/// or for a single output, `1` if it is at least `0.5` and `0` otherwise.
+pub enum Metric {
+    /// ### Accuracy :
+    /// Proportion of samples whose class is correctly predicted.
+    Accuracy,
+    /// ### Precision :
+    /// Proportion of the samples predicted in a class that really are in it.\
+    /// Averaged over the classes, or for a single output, computed on the class `1`.
+    Precision,
+    /// ### Recall :
+    /// Proportion of the samples of a class that are predicted in it.\
+    /// Averaged over the classes, or for a single output, computed on the class `1`.
+    Recall,
+    /// ### F1 score :
+    /// Harmonic mean of the precision and the recall of each class.\
+    /// Averaged over the classes, or for a single output, computed on the class `1`.
+    F1,
+    /// ### Mean absolute error :
+    /// `mean( |ŷ - y| )`
+    MeanAbsoluteError,
+    /// ### Root mean squared error :
+    /// `sqrt( mean( (ŷ - y)² ) )`
+    RootMeanSquaredError,
+    /// ### Coefficient of determination :
+    /// `R² = 1 - sum( (ŷ - y)² ) / sum( (y - mean(y))² )`, `mean(y)` being computed for each output.
+    R2,
+}
+impl Metric {
+    /// Returns the name of the metric, used as its key in `Evaluation` and `History`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::Accuracy => "accuracy",
+            Metric::Precision => "precision",
+            Metric::Recall => "recall",
+            Metric::F1 => "f1",
+            Metric::MeanAbsoluteError => "mae",
+            Metric::RootMeanSquaredError => "rmse",
+            Metric::R2 => "r2",
+        }
+    }
+
+    /// Returns the value of the metric for the prediction `y_pred` against the expected `y`.
+    pub fn compute(&self, y_pred: &Array2<f64>, y: &Array2<f64>) -> f64 {
+        match self {
+            Metric::Accuracy => metrics::accuracy(y_pred, y),
+            Metric::Precision => metrics::precision(y_pred, y),
+            Metric::Recall => metrics::recall(y_pred, y),
+            Metric::F1 => metrics::f1(y_pred, y),
+            Metric::MeanAbsoluteError => metrics::mae(y_pred, y),
+            Metric::RootMeanSquaredError => metrics::rmse(y_pred, y),
+            Metric::R2 => metrics::r2(y_pred, y),
         }
     }
 }
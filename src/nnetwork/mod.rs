@@ -1,21 +1,41 @@
 //! ### NNetwork
 //! Provides most parts of `NNetwork` struct, it is the main class of the library.
 
-use crate::{log::*, Array2, NNetwork, Weights};
+use crate::{
+    callbacks, history, interfaces::check_aligned, log::*, maths, Activation, Array1, Array2,
+    DatasTrain, Dense, Evaluation, GradientCheck, Loss, Monitor, NNetwork, Phase, PrivateCalls,
+    SeedableRng, SpitzError, StdRng,
+};
+use std::mem;
+use std::time::Instant;
 
 impl Default for NNetwork {
     fn default() -> Self {
         Self {
             architecture: Default::default(),
-            weights: Default::default(),
             datas_raw: Default::default(),
             datas: Default::default(),
-            grads: Default::default(),
+            optimizer: Box::new(crate::optimizers::Sgd::new()),
+            schedule: Box::new(crate::schedules::Constant),
+            rng: StdRng::from_entropy(),
+            phase: Default::default(),
+            history: Default::default(),
             learning_rate: crate::DEFAULT_LN,
             epochs: crate::DEFAULT_EPOCHS,
+            shuffle: true,
+            drop_last: false,
             batches: crate::DEFAULT_BATCHES,
+            loss: Default::default(),
+            initializer: None,
+            regularizer: None,
+            constraint: None,
+            metrics: Vec::new(),
+            evaluate_each_epoch: false,
+            early_stopping: None,
+            callbacks: Vec::new(),
             epoch: 0,
             batch: 0,
+            loaded: false,
         }
     }
 }
@@ -27,86 +47,433 @@ impl NNetwork {
     }
 
     /// ## Feed forward the network
-    /// Runs the network with given `input`, and results `output`.
+    /// Runs the network with given `input`, and results `output`.\
+    /// The network is run as in the `Phase::Inference` phase : dropouts are not applied,
+    /// and batch normalizations use their running statistics.
     pub fn feed_forward(&self, inputs: &Array2<f64>) -> Vec<Array2<f64>> {
         // TODO create `x` during init so we don't need to create again it during each batch of each epoch
         let mut x = vec![inputs.clone()];
-        let mut z: Array2<f64>;
-        let mut y: Array2<f64>;
-
-        for (id, w) in self.weights.iter().enumerate() {
-            // Weighted average `z = w · x`
-            z = x.last().unwrap().dot(w);
-            // Activation function `y = g(x)`
-            y = (self.architecture.layers[id].activation)(z, false);
+        for layer in &self.architecture.layers {
+            let y = layer.infer(x.last().unwrap());
             // Append `y` to previous layers
             x.push(y);
         }
         x
     }
 
-    /// Calculate weights errors
-    pub fn grads(&mut self) {
-        let data = &self.datas.train[self.batch];
+    /// Returns the current phase of the network : `Phase::Training` during `fit`, `Phase::Inference` else.
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
 
-        // Forward propagation to get network datas
-        let y: Weights = self.feed_forward(&data.x);
+    /// ## Predict with the network
+    /// Runs the network with given `inputs` and returns only its output layer.\
+    /// Unlike `feed_forward`, intermediate layers are not kept.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::UninitializedNetwork` if the network was not initialized.\
+    /// Returns `SpitzError::IncompatibleDataWidth` if the width of `inputs` is not the size of the input layer.
+    pub fn predict(&self, inputs: &Array2<f64>) -> Result<Array2<f64>, SpitzError> {
+        let layers = &self.architecture.layers;
+        if !self.parameters_match_architecture() {
+            return Err(SpitzError::UninitializedNetwork);
+        }
+        if inputs.ncols() != layers[0].input_size() {
+            return Err(SpitzError::IncompatibleDataWidth {
+                datas: "inputs",
+                expected: layers[0].input_size(),
+                found: inputs.ncols(),
+            });
+        }
 
-        // Calculate global error
-        let mut delta: Array2<f64> = (y.last().unwrap() - &data.y).mapv(|a| a.powi(2));
+        let mut y = layers[0].infer(inputs);
+        for layer in layers.iter().skip(1) {
+            y = layer.infer(&y);
+        }
+        Ok(y)
+    }
 
-        // Calculate error of output weights layer
-        self.grads[self.weights.len() - 1] = y[y.len() - 1].t().dot(&delta);
+    /// ## Predict classes with the network
+    /// Returns, for each row of `inputs`, the index of the output with the highest value.
+    ///
+    /// ### Errors
+    /// Returns an error in the same cases as `predict`.
+    pub fn predict_classes(&self, inputs: &Array2<f64>) -> Result<Array1<usize>, SpitzError> {
+        Ok(self
+            .predict(inputs)?
+            .genrows()
+            .into_iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .fold(0, |best, (id, &v)| if v > row[best] { id } else { best })
+            })
+            .collect())
+    }
 
-        // Backpropagation of error
-        // TODO make a for-loop for efficiency and readability
-        let mut loop_lenght = self.grads.len() - 1;
-        while loop_lenght > 0 {
-            // FIXME verify that the activation used is the good one and the same as the derivative used
-            delta = delta.dot(&self.weights[loop_lenght].t())
-                * (self.architecture.layers[loop_lenght].activation)(y[loop_lenght].clone(), true);
+    /// ## Predict probabilities with the network
+    /// Returns the output of the network as probabilities :
+    /// `Softmax` and `Sigmoid` outputs are already probabilities and returned as is,
+    /// other outputs are considered as logits and normalized with a softmax.\
+    /// A single output is the logit of the class `1` : it is normalized with a sigmoid instead.
+    ///
+    /// ### Errors
+    /// Returns an error in the same cases as `predict`.
+    pub fn predict_proba(&self, inputs: &Array2<f64>) -> Result<Array2<f64>, SpitzError> {
+        let output = self.predict(inputs)?;
+        Ok(
+            match self.architecture.layers.last().and_then(|l| l.activation()) {
+                Some(Activation::Softmax) | Some(Activation::Sigmoid) => output,
+                _ if output.ncols() == 1 => maths::activations::sigmoid(output, false),
+                _ => maths::activations::softmax(output, false),
+            },
+        )
+    }
 
-            self.grads[loop_lenght - 1] = y[loop_lenght - 1].t().dot(&delta);
-            loop_lenght -= 1;
+    /// ## Evaluate the network
+    /// Runs the network on the testing datas imported before `init`, and returns its loss and the metrics set with `set_metrics`.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::MissingTestDatas` if no testing datas were imported.\
+    /// Returns an error in the same cases as `evaluate_on`.
+    pub fn evaluate(&self) -> Result<Evaluation, SpitzError> {
+        if self.datas.test.x.is_empty() {
+            return Err(SpitzError::MissingTestDatas);
         }
+        self.evaluate_on(&self.datas.test.x, &self.datas.test.y)
+    }
 
-        let len = data.x.len() as f64;
-        for layer in &mut self.grads {
-            layer.mapv(|x| x / len);
+    /// ## Evaluate the network on given datas
+    /// Runs the network on `x`, and returns its loss and the metrics set with `set_metrics` against `y`.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::ShapeMismatch` if `x` and `y` do not have the same number of rows.\
+    /// Returns `SpitzError::IncompatibleDataWidth` if the width of `y` is not the size of the output layer.\
+    /// Returns an error in the same cases as `predict`.
+    pub fn evaluate_on(&self, x: &Array2<f64>, y: &Array2<f64>) -> Result<Evaluation, SpitzError> {
+        check_aligned(x, y)?;
+        let output = self.predict(x)?;
+        if y.ncols() != output.ncols() {
+            return Err(SpitzError::IncompatibleDataWidth {
+                datas: "outputs",
+                expected: output.ncols(),
+                found: y.ncols(),
+            });
         }
+
+        Ok(Evaluation {
+            loss: self.loss.loss(&output, y),
+            metrics: self
+                .metrics
+                .iter()
+                .map(|metric| (metric.name().to_string(), metric.compute(&output, y)))
+                .collect(),
+        })
+    }
+
+    /// Calculate the gradients of every layer for the `data` batch
+    /// and returns its loss, including the weights penalties.
+    pub fn grads(&mut self, data: &DatasTrain) -> f64 {
+        let phase = self.phase;
+        let layers = &mut self.architecture.layers;
+
+        // Forward propagation, each layer keeping what its backpropagation needs
+        let mut output = data.x.clone();
+        for layer in layers.iter_mut() {
+            output = layer.forward(&output, phase, &mut self.rng);
+        }
+        let last = layers.len() - 1;
+
+        // Calculate global error and its derivative
+        let loss = self.loss.loss(&output, &data.y);
+        // Softmax and cross-entropy derivatives simplify into `ŷ - y`, with respect to the weighted sums
+        let fused = layers[last].as_any().is::<Dense>()
+            && layers[last].activation() == Some(&Activation::Softmax)
+            && self.loss == Loss::CategoricalCrossEntropy;
+        let mut grad = if fused {
+            (&output - &data.y) / data.y.nrows().max(1) as f64
+        } else {
+            self.loss.derivative(&output, &data.y)
+        };
+
+        // Backpropagation of error, from the output layer
+        for (id, layer) in layers.iter_mut().enumerate().rev() {
+            grad = match layer.as_any_mut().downcast_mut::<Dense>() {
+                Some(dense) if fused && id == last => dense.backward_weighted_sums(&grad),
+                _ => layer.backward(&grad),
+            };
+        }
+
+        // Penalize the weights magnitude
+        let mut penalty = 0.;
+        for layer in layers.iter_mut() {
+            penalty += layer.regularize(self.regularizer.as_ref());
+        }
+
+        loss + penalty
+    }
+
+    /// ## Check the gradients
+    /// Compares the gradients computed by backpropagation on the `x` and `y` batch, in the `Phase::Training` phase,
+    /// with the finite differences `(L(p + eps) - L(p - eps)) / (2 * eps)` of the loss for every value `p`
+    /// of every parameter.\
+    /// Returns the relative error of each parameter, which should be about `1e-7` or less with `eps = 1e-5`.
+    ///
+    /// Dropouts draw the same masks for every evaluation of the loss,
+    /// and the network is left as it was, running statistics included.
+    ///
+    /// ### Errors
+    /// Returns an error in the same cases as `evaluate_on`.
+    pub fn gradient_check(
+        &mut self,
+        x: &Array2<f64>,
+        y: &Array2<f64>,
+        eps: f64,
+    ) -> Result<Vec<GradientCheck>, SpitzError> {
+        self.evaluate_on(x, y)?;
+        let data = DatasTrain {
+            x: x.clone(),
+            y: y.clone(),
+        };
+        let layers = self.architecture.layers.clone();
+        let rng = self.rng.clone();
+        let phase = mem::replace(&mut self.phase, Phase::Training);
+
+        self.grads(&data);
+        let analytic: Vec<Vec<Array2<f64>>> = self
+            .architecture
+            .layers
+            .iter()
+            .map(|layer| layer.gradients().into_iter().cloned().collect())
+            .collect();
+
+        let mut checks = Vec::new();
+        for (id, gradients) in analytic.iter().enumerate() {
+            for (parameter, gradient) in gradients.iter().enumerate() {
+                let mut numerical = Array2::zeros(gradient.raw_dim());
+                for (index, value) in numerical.indexed_iter_mut() {
+                    let mut loss_with = |delta: f64| {
+                        self.rng = rng.clone();
+                        self.architecture.layers[id].parameters_mut()[parameter][index] += delta;
+                        let loss = self.grads(&data);
+                        self.architecture.layers[id].parameters_mut()[parameter][index] -= delta;
+                        loss
+                    };
+                    *value = (loss_with(eps) - loss_with(-eps)) / (2. * eps);
+                }
+
+                let norm = |a: &Array2<f64>| a.mapv(|v| v.powi(2)).sum().sqrt();
+                let sum = norm(gradient) + norm(&numerical);
+                checks.push(GradientCheck {
+                    layer: id,
+                    parameter,
+                    relative_error: if sum == 0. {
+                        0.
+                    } else {
+                        norm(&(gradient - &numerical)) / sum
+                    },
+                });
+            }
+        }
+
+        self.architecture.layers = layers;
+        self.rng = rng;
+        self.phase = phase;
+        Ok(checks)
     }
 
     /// ## Train the network
-    /// Trains the network over the previously given datasets
+    /// Trains the network over the previously given datasets.\
+    /// The losses, learning rate and duration of each epoch are recorded in a new `History`,
+    /// available with `get_history` once trained.\
+    /// The hooks of the callbacks added with `add_callback` are called all along the training.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::UninitializedNetwork` if the network was not initialized.\
+    /// Returns `SpitzError::MissingTrainingDatas` if no training datas were imported.\
+    /// Returns `SpitzError::InvalidBatchSize` if the batches are empty, or would all be dropped with `set_drop_last`.\
+    /// Returns `SpitzError::MissingTestDatas` if the early stopping or the learning rate schedule
+    /// monitors the testing datas but none were imported.
     // TODO make training a concurrent process
-    pub fn fit(&mut self) -> &mut Self {
-        for epoch in 0..self.epochs {
+    pub fn fit(&mut self) -> Result<&mut Self, SpitzError> {
+        if !self.parameters_match_architecture() {
+            return Err(SpitzError::UninitializedNetwork);
+        }
+        if self.datas_raw.train_x.is_empty() || self.datas.order.is_empty() {
+            return Err(SpitzError::MissingTrainingDatas);
+        }
+        if self.batches == 0 || (self.drop_last && self.batches > self.datas.order.len()) {
+            return Err(SpitzError::InvalidBatchSize);
+        }
+        let monitors: Vec<&Monitor> = self
+            .early_stopping
+            .iter()
+            .map(|e| &e.monitor)
+            .chain(self.schedule.monitor())
+            .collect();
+        // Everything but the training loss is monitored on the testing datas
+        if self.datas.test.x.is_empty() && monitors.iter().any(|&m| *m != Monitor::TrainLoss) {
+            return Err(SpitzError::MissingTestDatas);
+        }
+        // Metrics must be evaluated at each epoch to be monitored
+        let evaluate =
+            self.evaluate_each_epoch || monitors.iter().any(|m| matches!(m, Monitor::Metric(_)));
+        if let Some(early_stopping) = &mut self.early_stopping {
+            early_stopping.reset();
+        }
+        self.schedule.reset();
+        self.history = Default::default();
+        self.phase = Phase::Training;
+
+        // Callbacks are taken out of the network during training, so that they can read it
+        let mut callbacks = std::mem::take(&mut self.callbacks);
+        let mut stop = callbacks::notify(&mut callbacks, |c| c.on_train_begin(self));
+
+        let mut epoch = 0;
+        while !stop && epoch < self.epochs {
             self.epoch = epoch;
+            let start = Instant::now();
+
+            let learning_rate = self.schedule.learning_rate(self.learning_rate, epoch);
+
+            debug!("epoch n°{}, learning rate = {}", self.epoch, learning_rate);
 
-            debug!("epoch n°{}", self.epoch);
+            if callbacks::notify(&mut callbacks, |c| c.on_epoch_begin(self, epoch)) {
+                break;
+            }
+
+            // Visit the training datas in a new order at each epoch
+            if self.shuffle {
+                self.datas.shuffle(&mut self.rng);
+            }
+            let batches = self.datas.batches(self.batches, self.drop_last);
 
-            for batch in 0..self.datas.train.len() {
+            let mut epoch_loss = 0.;
+            let mut trained = 0;
+            for (batch, indices) in batches.iter().enumerate() {
                 self.batch = batch;
 
                 trace!("batch n°{}", self.batch);
 
-                // Get errors for each layer
-                self.grads();
+                stop = callbacks::notify(&mut callbacks, |c| c.on_batch_begin(self, epoch, batch));
+                if stop {
+                    break;
+                }
+
+                let loss = self.train_batch(indices, learning_rate);
+                epoch_loss += loss;
+                trained += 1;
+
+                stop =
+                    callbacks::notify(&mut callbacks, |c| c.on_batch_end(self, epoch, batch, loss));
+                if stop {
+                    break;
+                }
+            }
+            if trained == 0 && !batches.is_empty() {
+                break;
+            }
+
+            // Record the epoch
+            let train_loss = epoch_loss / trained.max(1) as f64;
+            let (test_loss, metrics) = if evaluate {
+                match self.evaluate() {
+                    Ok(evaluation) => (Some(evaluation.loss), evaluation.metrics),
+                    Err(_) => (None, Default::default()),
+                }
+            } else {
+                (self.test_loss(), Default::default())
+            };
+            debug!("loss = {:.6}", train_loss);
+            if let Some(test_loss) = test_loss {
+                debug!("test loss = {:.6}", test_loss);
+            }
+            for (name, value) in &metrics {
+                debug!("{} = {:.6}", name, value);
+            }
+            self.history.epochs.push(history::Epoch {
+                epoch,
+                train_loss,
+                test_loss,
+                metrics,
+                learning_rate,
+                duration: start.elapsed(),
+            });
+
+            let logs = self.history.epochs.last().unwrap();
+            self.schedule.on_epoch_end(logs);
+            stop |= callbacks::notify(&mut callbacks, |c| c.on_epoch_end(self, logs));
+            if let Some(early_stopping) = &mut self.early_stopping {
+                if early_stopping.update(logs, &self.architecture.layers) {
+                    debug!("early stopping at epoch n°{}", epoch);
+                    stop = true;
+                }
+            }
+            epoch += 1;
+        }
+
+        // Go back to the best epoch if asked
+        if let Some(early_stopping) = &mut self.early_stopping {
+            if early_stopping.restore_best(&mut self.architecture.layers) {
+                debug!(
+                    "restored weights of epoch n°{}",
+                    early_stopping.best_epoch().unwrap_or(0)
+                );
+            }
+        }
+
+        self.phase = Phase::Inference;
+        for callback in &mut callbacks {
+            callback.on_train_end(self);
+        }
+        self.callbacks = callbacks;
+        Ok(self)
+    }
+
+    /// Trains the network on the training samples at given `indices` with given `learning_rate`,
+    /// and returns the loss of this batch.
+    fn train_batch(&mut self, indices: &[usize], learning_rate: f64) -> f64 {
+        // Get errors for each layer
+        let data = self.datas_raw.train_batch(indices);
+        let loss = self.grads(&data);
 
-                // Update weights for each layer
-                for id in 0..self.weights.len() - 1 {
-                    self.weights[id] =
-                        &self.weights[id] - &self.grads[id].mapv(|x| x * self.learning_rate);
+        // Update the parameters of each layer, identified by their position in the network
+        self.optimizer.step();
+        let mut id = 0;
+        for layer in &mut self.architecture.layers {
+            let grads: Vec<Array2<f64>> = layer.gradients().into_iter().cloned().collect();
+            let weights = layer.weights_index();
+            let params = layer.parameters_mut().into_iter().zip(&grads);
+            for (index, (param, grad)) in params.enumerate() {
+                // Weights are told apart from the other parameters, e.g. for weight decay
+                if weights == Some(index) {
+                    self.optimizer
+                        .update_weights(id, param, grad, learning_rate);
+                } else {
+                    self.optimizer.update(id, param, grad, learning_rate);
                 }
+                id += 1;
             }
+            layer.constrain(self.constraint.as_ref());
         }
-        self
+        loss
+    }
+
+    /// Returns the loss over the testing datas, if some were imported.
+    fn test_loss(&self) -> Option<f64> {
+        if self.datas.test.x.is_empty() {
+            return None;
+        }
+        let output = self.predict(&self.datas.test.x).ok()?;
+        Some(self.loss.loss(&output, &self.datas.test.y))
     }
 
-    /// Print weights (used mostly for debugging).
+    /// Print weights and biases (used mostly for debugging).
     pub fn print_weights(&mut self) -> &mut Self {
-        for (id, w) in self.weights.iter().enumerate() {
-            println!("Layer {} to {}\n{:7.4}\n", id, id + 1, w);
+        let layers = self.architecture.layers.iter();
+        let parameters = layers.filter_map(|l| Some((l.weights()?, l.biases()?)));
+        for (id, (w, b)) in parameters.enumerate() {
+            println!("Layer {} to {}\n{:7.4}\nBias\n{:7.4}\n", id, id + 1, w, b);
         }
         println!("\n");
         self
@@ -0,0 +1,352 @@
+//! ### Optimizers
+//! Provides the `Optimizer` trait, used by `NNetwork::fit` to update each parameter from its gradient,
+//! and its implementations :
+//! - `Sgd`, stochastic gradient descent, with optional momentum or Nesterov momentum ;
+//! - `RmsProp` ;
+//! - `Adagrad` ;
+//! - `Adam` and `AdamW` (Adam with decoupled weight decay).
+//!
+//! Every parameter is identified by an `id`, so that optimizers can keep a state for each one.
+
+use crate::Array2;
+use std::collections::HashMap;
+
+/// Updates the network parameters from their gradients.
+pub trait Optimizer {
+    /// Called once per batch, before its parameters are updated.
+    fn step(&mut self) {}
+    /// Updates `param` in place from its gradient `grad`.\
+    /// `id` identifies the parameter across batches and epochs.
+    fn update(
+        &mut self,
+        id: usize,
+        param: &mut Array2<f64>,
+        grad: &Array2<f64>,
+        learning_rate: f64,
+    );
+    /// Updates the weights `param` in place from their gradient `grad`, like `update`.\
+    /// Only weights are subject to weight decay : biases and the other parameters are updated with `update`.
+    fn update_weights(
+        &mut self,
+        id: usize,
+        param: &mut Array2<f64>,
+        grad: &Array2<f64>,
+        learning_rate: f64,
+    ) {
+        self.update(id, param, grad, learning_rate);
+    }
+    /// Forgets every state accumulated during training (called by `init`).
+    fn reset(&mut self);
+    /// Returns a boxed copy of the optimizer, used to clone the network.
+    fn box_clone(&self) -> Box<dyn Optimizer>;
+}
+
+impl Clone for Box<dyn Optimizer> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Returns the state of parameter `id`, initialized at zero with the shape of `like`.
+fn state<'a>(
+    states: &'a mut HashMap<usize, Array2<f64>>,
+    id: usize,
+    like: &Array2<f64>,
+) -> &'a mut Array2<f64> {
+    let state = states
+        .entry(id)
+        .or_insert_with(|| Array2::zeros(like.raw_dim()));
+    // Parameters may have been reshaped by a new `init`
+    if state.raw_dim() != like.raw_dim() {
+        *state = Array2::zeros(like.raw_dim());
+    }
+    state
+}
+
+// * Sgd optimizer
+/// ### Stochastic gradient descent :
+/// `v = momentum * v - lr * g`, then `p += v`.\
+/// With Nesterov momentum, `p += momentum * v - lr * g` instead.\
+/// Without momentum (the default), it is the plain `p -= lr * g`.
+#[derive(Clone, Debug, Default)]
+pub struct Sgd {
+    pub momentum: f64,
+    pub nesterov: bool,
+    velocities: HashMap<usize, Array2<f64>>,
+}
+impl Sgd {
+    /// Returns a plain gradient descent optimizer.
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Returns a gradient descent optimizer with given `momentum`, usually `0.9`.
+    pub fn with_momentum(momentum: f64) -> Self {
+        Self {
+            momentum,
+            ..Default::default()
+        }
+    }
+    /// Returns a gradient descent optimizer with given Nesterov `momentum`, usually `0.9`.
+    pub fn with_nesterov(momentum: f64) -> Self {
+        Self {
+            momentum,
+            nesterov: true,
+            ..Default::default()
+        }
+    }
+}
+impl Optimizer for Sgd {
+    fn update(
+        &mut self,
+        id: usize,
+        param: &mut Array2<f64>,
+        grad: &Array2<f64>,
+        learning_rate: f64,
+    ) {
+        if self.momentum == 0. {
+            param.scaled_add(-learning_rate, grad);
+            return;
+        }
+        let v = state(&mut self.velocities, id, param);
+        *v *= self.momentum;
+        v.scaled_add(-learning_rate, grad);
+        if self.nesterov {
+            param.scaled_add(self.momentum, v);
+            param.scaled_add(-learning_rate, grad);
+        } else {
+            *param += &*v;
+        }
+    }
+    fn reset(&mut self) {
+        self.velocities.clear();
+    }
+    fn box_clone(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+}
+
+// * RmsProp optimizer
+/// ### RMSProp :
+/// `s = rho * s + (1 - rho) * g²`, then `p -= lr * g / (sqrt(s) + epsilon)`.\
+/// Defaults are `rho = 0.9` and `epsilon = 1e-8`.
+#[derive(Clone, Debug)]
+pub struct RmsProp {
+    pub rho: f64,
+    pub epsilon: f64,
+    caches: HashMap<usize, Array2<f64>>,
+}
+impl Default for RmsProp {
+    fn default() -> Self {
+        Self::new(0.9, 1e-8)
+    }
+}
+impl RmsProp {
+    /// Returns a RMSProp optimizer with given decay `rho` and `epsilon`.
+    pub fn new(rho: f64, epsilon: f64) -> Self {
+        Self {
+            rho,
+            epsilon,
+            caches: HashMap::new(),
+        }
+    }
+}
+impl Optimizer for RmsProp {
+    fn update(
+        &mut self,
+        id: usize,
+        param: &mut Array2<f64>,
+        grad: &Array2<f64>,
+        learning_rate: f64,
+    ) {
+        let (rho, epsilon) = (self.rho, self.epsilon);
+        let s = state(&mut self.caches, id, param);
+        s.zip_mut_with(grad, |s, &g| *s = rho * *s + (1. - rho) * g * g);
+        ndarray::Zip::from(param)
+            .and(grad)
+            .and(&*s)
+            .apply(|p, &g, &s| *p -= learning_rate * g / (s.sqrt() + epsilon));
+    }
+    fn reset(&mut self) {
+        self.caches.clear();
+    }
+    fn box_clone(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+}
+
+// * Adagrad optimizer
+/// ### Adagrad :
+/// `s += g²`, then `p -= lr * g / (sqrt(s) + epsilon)`.\
+/// Default is `epsilon = 1e-8`.
+#[derive(Clone, Debug)]
+pub struct Adagrad {
+    pub epsilon: f64,
+    caches: HashMap<usize, Array2<f64>>,
+}
+impl Default for Adagrad {
+    fn default() -> Self {
+        Self::new(1e-8)
+    }
+}
+impl Adagrad {
+    /// Returns an Adagrad optimizer with given `epsilon`.
+    pub fn new(epsilon: f64) -> Self {
+        Self {
+            epsilon,
+            caches: HashMap::new(),
+        }
+    }
+}
+impl Optimizer for Adagrad {
+    fn update(
+        &mut self,
+        id: usize,
+        param: &mut Array2<f64>,
+        grad: &Array2<f64>,
+        learning_rate: f64,
+    ) {
+        let epsilon = self.epsilon;
+        let s = state(&mut self.caches, id, param);
+        s.zip_mut_with(grad, |s, &g| *s += g * g);
+        ndarray::Zip::from(param)
+            .and(grad)
+            .and(&*s)
+            .apply(|p, &g, &s| *p -= learning_rate * g / (s.sqrt() + epsilon));
+    }
+    fn reset(&mut self) {
+        self.caches.clear();
+    }
+    fn box_clone(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+}
+
+// * Adam optimizer
+/// ### Adam :
+/// `m = beta1 * m + (1 - beta1) * g` and `v = beta2 * v + (1 - beta2) * g²`,\
+/// then `p -= lr * m̂ / (sqrt(v̂) + epsilon)` with `m̂` and `v̂` corrected from their initialization bias.\
+/// Defaults are `beta1 = 0.9`, `beta2 = 0.999` and `epsilon = 1e-8`.
+#[derive(Clone, Debug)]
+pub struct Adam {
+    pub beta1: f64,
+    pub beta2: f64,
+    pub epsilon: f64,
+    t: i32,
+    moments: HashMap<usize, (Array2<f64>, Array2<f64>)>,
+}
+impl Default for Adam {
+    fn default() -> Self {
+        Self::new(0.9, 0.999, 1e-8)
+    }
+}
+impl Adam {
+    /// Returns an Adam optimizer with given `beta1`, `beta2` and `epsilon`.
+    pub fn new(beta1: f64, beta2: f64, epsilon: f64) -> Self {
+        Self {
+            beta1,
+            beta2,
+            epsilon,
+            t: 0,
+            moments: HashMap::new(),
+        }
+    }
+}
+impl Optimizer for Adam {
+    fn step(&mut self) {
+        self.t += 1;
+    }
+    fn update(
+        &mut self,
+        id: usize,
+        param: &mut Array2<f64>,
+        grad: &Array2<f64>,
+        learning_rate: f64,
+    ) {
+        let (beta1, beta2, epsilon) = (self.beta1, self.beta2, self.epsilon);
+        // Bias corrections, `t` is at least `1` even if `step` was not called
+        let t = self.t.max(1);
+        let correction1 = 1. - beta1.powi(t);
+        let correction2 = 1. - beta2.powi(t);
+
+        let (m, v) = self.moments.entry(id).or_insert_with(|| {
+            (
+                Array2::zeros(param.raw_dim()),
+                Array2::zeros(param.raw_dim()),
+            )
+        });
+        if m.raw_dim() != param.raw_dim() {
+            *m = Array2::zeros(param.raw_dim());
+            *v = Array2::zeros(param.raw_dim());
+        }
+        m.zip_mut_with(grad, |m, &g| *m = beta1 * *m + (1. - beta1) * g);
+        v.zip_mut_with(grad, |v, &g| *v = beta2 * *v + (1. - beta2) * g * g);
+        ndarray::Zip::from(param)
+            .and(&*m)
+            .and(&*v)
+            .apply(|p, &m, &v| {
+                *p -= learning_rate * (m / correction1) / ((v / correction2).sqrt() + epsilon)
+            });
+    }
+    fn reset(&mut self) {
+        self.t = 0;
+        self.moments.clear();
+    }
+    fn box_clone(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+}
+
+// * AdamW optimizer
+/// ### AdamW :
+/// Adam with decoupled weight decay : `p -= lr * weight_decay * p` is applied to the weights before the Adam update,
+/// biases and other parameters only get the Adam update.\
+/// Defaults are the ones of `Adam`, with `weight_decay = 0.01`.
+#[derive(Clone, Debug)]
+pub struct AdamW {
+    pub adam: Adam,
+    pub weight_decay: f64,
+}
+impl Default for AdamW {
+    fn default() -> Self {
+        Self::new(0.9, 0.999, 1e-8, 0.01)
+    }
+}
+impl AdamW {
+    /// Returns an AdamW optimizer with given `beta1`, `beta2`, `epsilon` and `weight_decay`.
+    pub fn new(beta1: f64, beta2: f64, epsilon: f64, weight_decay: f64) -> Self {
+        Self {
+            adam: Adam::new(beta1, beta2, epsilon),
+            weight_decay,
+        }
+    }
+}
+impl Optimizer for AdamW {
+    fn step(&mut self) {
+        self.adam.step();
+    }
+    fn update(
+        &mut self,
+        id: usize,
+        param: &mut Array2<f64>,
+        grad: &Array2<f64>,
+        learning_rate: f64,
+    ) {
+        self.adam.update(id, param, grad, learning_rate);
+    }
+    fn update_weights(
+        &mut self,
+        id: usize,
+        param: &mut Array2<f64>,
+        grad: &Array2<f64>,
+        learning_rate: f64,
+    ) {
+        *param *= 1. - learning_rate * self.weight_decay;
+        self.adam.update(id, param, grad, learning_rate);
+    }
+    fn reset(&mut self) {
+        self.adam.reset();
+    }
+    fn box_clone(&self) -> Box<dyn Optimizer> {
+        Box::new(self.clone())
+    }
+}
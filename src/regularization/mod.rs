@@ -0,0 +1,169 @@
+//! ### Regularization
+//! Provides `Regularizer`, a penalty on the magnitude of the weights added to the training loss and to its gradients,
+//! and `Constraint`, applied to the weights after each update.\
+//! Both can be set for every layer (`set_regularizer`, `set_constraint`) or for a single one
+//! (`set_layer_regularizer`, `set_layer_constraint`) ; biases are never regularized nor constrained.
+//!
+//! Also provides `Dropout`, that randomly masks the outputs of a layer during training (see `add_dropout`).
+
+use crate::maths::activations::{SELU_ALPHA, SELU_SCALE};
+use crate::{Array, Array2, Axis, Phase, RandomExt};
+use ndarray_rand::rand::Rng;
+use ndarray_rand::rand_distr::Bernoulli;
+
+#[derive(Clone, Debug, PartialEq)]
+/// List the different implemented weights penalties.
+pub enum Regularizer {
+    /// ### L1 (lasso) penalty :
+    /// Pushes weights towards exactly `0`, the parameter is its factor `l1`.
+    ///
+    /// #### Mathematically :
+    /// `P = l1 * sum( |w| )`
+    /// #### Derivative :
+    /// `∂P/∂w = l1 * sign(w)`
+    L1(f64),
+    /// ### L2 (ridge, or weight decay) penalty :
+    /// Keeps weights small, the parameter is its factor `l2`.
+    ///
+    /// #### Mathematically :
+    /// `P = l2 * sum( w² )`
+    /// #### Derivative :
+    /// `∂P/∂w = 2 * l2 * w`
+    L2(f64),
+    /// ### Elastic-net penalty :
+    /// Sum of the L1 and L2 penalties, the parameters are `l1` and `l2`.
+    ElasticNet(f64, f64),
+}
+impl Regularizer {
+    /// Returns the factors `(l1, l2)` of the penalty.
+    fn factors(&self) -> (f64, f64) {
+        match *self {
+            Regularizer::L1(l1) => (l1, 0.),
+            Regularizer::L2(l2) => (0., l2),
+            Regularizer::ElasticNet(l1, l2) => (l1, l2),
+        }
+    }
+
+    /// Returns the penalty of `weights`.
+    pub fn penalty(&self, weights: &Array2<f64>) -> f64 {
+        let (l1, l2) = self.factors();
+        weights.iter().map(|w| l1 * w.abs() + l2 * w * w).sum()
+    }
+
+    /// Returns the derivative of the penalty with respect to each weight.
+    pub fn derivative(&self, weights: &Array2<f64>) -> Array2<f64> {
+        let (l1, l2) = self.factors();
+        weights.mapv(|w| {
+            let sign = if w > 0. {
+                1.
+            } else if w < 0. {
+                -1.
+            } else {
+                0.
+            };
+            l1 * sign + 2. * l2 * w
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// List the different implemented weights constraints.\
+/// They apply to the incoming weights of each neuron, that is each column of the weights matrix.
+pub enum Constraint {
+    /// ### Max-norm constraint :
+    /// Rescales the incoming weights of each neuron whose L2 norm is above the given maximum.
+    MaxNorm(f64),
+    /// ### Unit-norm constraint :
+    /// Rescales the incoming weights of each neuron to a L2 norm of `1`.
+    UnitNorm,
+}
+impl Constraint {
+    /// Applies the constraint in place to `weights`.
+    pub fn apply(&self, weights: &mut Array2<f64>) {
+        for mut column in weights.axis_iter_mut(Axis(1)) {
+            let norm = column.dot(&column).sqrt();
+            let target = match *self {
+                Constraint::MaxNorm(max) => norm.min(max),
+                Constraint::UnitNorm => 1.,
+            };
+            if norm > 0. && norm != target {
+                column.mapv_inplace(|w| w * target / norm);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// List the different implemented dropouts, applied to the outputs of a layer.\
+/// The parameter is the `rate` of outputs dropped, in `[0, 1)`.\
+/// They only act while the network is in the `Phase::Training` phase, and are an identity else.
+pub enum Dropout {
+    /// ### Dropout :
+    /// Sets each output to `0` with probability `rate`, and scales the others by `1 / (1 - rate)`
+    /// to keep their expected value.
+    Standard(f64),
+    /// ### Alpha dropout :
+    /// Sets each output to the negative saturation value of SELU with probability `rate`,
+    /// then applies an affine transformation keeping the mean and variance of the outputs.\
+    /// To use with `SELU` networks.
+    Alpha(f64),
+}
+impl Dropout {
+    /// Returns the rate of outputs dropped.
+    pub fn rate(&self) -> f64 {
+        match *self {
+            Dropout::Standard(rate) | Dropout::Alpha(rate) => rate,
+        }
+    }
+
+    /// Returns the name of the dropout, used to save networks.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dropout::Standard(_) => "dropout",
+            Dropout::Alpha(_) => "alpha_dropout",
+        }
+    }
+
+    /// Returns the dropout called `name` with given `rate`, if it exists.
+    pub fn from_name(name: &str, rate: f64) -> Option<Dropout> {
+        match name {
+            "dropout" => Some(Dropout::Standard(rate)),
+            "alpha_dropout" => Some(Dropout::Alpha(rate)),
+            _ => None,
+        }
+    }
+
+    /// Applies the dropout to `outputs` if the network is in the `Phase::Training` phase, masks being drawn with `rng`.\
+    /// Returns the new outputs and, during training, their derivative with respect to `outputs`.
+    pub fn forward<R: Rng + ?Sized>(
+        &self,
+        outputs: Array2<f64>,
+        phase: Phase,
+        rng: &mut R,
+    ) -> (Array2<f64>, Option<Array2<f64>>) {
+        let rate = self.rate();
+        if phase == Phase::Inference || rate <= 0. {
+            return (outputs, None);
+        }
+        let keep = 1. - rate;
+        let mask: Array2<f64> =
+            Array::random_using(outputs.raw_dim(), Bernoulli::new(keep).unwrap(), rng)
+                .mapv(|kept: bool| kept as usize as f64);
+
+        match self {
+            Dropout::Standard(_) => {
+                let derivative = mask / keep;
+                (outputs * &derivative, Some(derivative))
+            }
+            Dropout::Alpha(_) => {
+                // Dropped outputs are set to the saturation value, then mean and variance are restored
+                let saturation = -SELU_SCALE * SELU_ALPHA;
+                let a = (keep + saturation.powi(2) * keep * rate).powf(-0.5);
+                let b = -a * rate * saturation;
+                let dropped = mask.mapv(|m| a * saturation * (1. - m) + b);
+                let derivative = mask * a;
+                (outputs * &derivative + dropped, Some(derivative))
+            }
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! ### Learning rate schedules
+//! Provides the `LearningRateSchedule` trait, used by `NNetwork::fit` to choose the learning rate of each epoch
+//! from the network learning rate, and its implementations :
+//! - `Constant`, the default ;
+//! - `StepDecay` and `ExponentialDecay` ;
+//! - `CosineAnnealing`, with warm restarts ;
+//! - `Warmup`, a linear warmup before another schedule ;
+//! - `OneCycle` ;
+//! - `ReduceOnPlateau`, driven by the evaluation loss.
+
+use crate::early_stopping::{Monitor, Plateau};
+use crate::history;
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Chooses the learning rate of each epoch.
+pub trait LearningRateSchedule: fmt::Debug {
+    /// Returns the learning rate to use during `epoch`, `initial` being the learning rate of the network.
+    fn learning_rate(&mut self, initial: f64, epoch: usize) -> f64;
+    /// Called at the end of each epoch with what was recorded for it.
+    fn on_epoch_end(&mut self, _logs: &history::Epoch) {}
+    /// Forgets every state accumulated during a previous training (called at the beginning of `fit`).
+    fn reset(&mut self) {}
+    /// Returns the quantity the schedule monitors, if any : a monitored metric is then evaluated at each epoch.
+    fn monitor(&self) -> Option<&Monitor> {
+        None
+    }
+    /// Returns a boxed copy of the schedule, used to clone the network.
+    fn box_clone(&self) -> Box<dyn LearningRateSchedule>;
+}
+
+impl Clone for Box<dyn LearningRateSchedule> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+// * Constant
+/// Keeps the learning rate of the network.
+#[derive(Clone, Debug, Default)]
+pub struct Constant;
+
+impl LearningRateSchedule for Constant {
+    fn learning_rate(&mut self, initial: f64, _epoch: usize) -> f64 {
+        initial
+    }
+    fn box_clone(&self) -> Box<dyn LearningRateSchedule> {
+        Box::new(self.clone())
+    }
+}
+
+// * Step decay
+/// Multiplies the learning rate by `gamma` every `step_size` epochs.
+#[derive(Clone, Debug)]
+pub struct StepDecay {
+    pub step_size: usize,
+    pub gamma: f64,
+}
+
+impl StepDecay {
+    pub fn new(step_size: usize, gamma: f64) -> Self {
+        Self { step_size, gamma }
+    }
+}
+
+impl LearningRateSchedule for StepDecay {
+    fn learning_rate(&mut self, initial: f64, epoch: usize) -> f64 {
+        initial * self.gamma.powi((epoch / self.step_size.max(1)) as i32)
+    }
+    fn box_clone(&self) -> Box<dyn LearningRateSchedule> {
+        Box::new(self.clone())
+    }
+}
+
+// * Exponential decay
+/// Multiplies the learning rate by `gamma` every epoch.
+#[derive(Clone, Debug)]
+pub struct ExponentialDecay {
+    pub gamma: f64,
+}
+
+impl ExponentialDecay {
+    pub fn new(gamma: f64) -> Self {
+        Self { gamma }
+    }
+}
+
+impl LearningRateSchedule for ExponentialDecay {
+    fn learning_rate(&mut self, initial: f64, epoch: usize) -> f64 {
+        initial * self.gamma.powi(epoch as i32)
+    }
+    fn box_clone(&self) -> Box<dyn LearningRateSchedule> {
+        Box::new(self.clone())
+    }
+}
+
+// * Cosine annealing
+/// Decreases the learning rate from its initial value to `min_learning_rate` along a half cosine
+/// over `period` epochs, then restarts (SGDR).\
+/// Each period is `period_mult` times longer than the previous one (`1` by default) ;
+/// use a period as long as the training to anneal without restarts.
+#[derive(Clone, Debug)]
+pub struct CosineAnnealing {
+    pub period: usize,
+    pub min_learning_rate: f64,
+    pub period_mult: f64,
+}
+
+impl CosineAnnealing {
+    pub fn new(period: usize, min_learning_rate: f64) -> Self {
+        Self {
+            period,
+            min_learning_rate,
+            period_mult: 1.,
+        }
+    }
+
+    /// Multiply the length of the period by `period_mult` at each restart.
+    pub fn with_period_mult(mut self, period_mult: f64) -> Self {
+        self.period_mult = period_mult.max(1.);
+        self
+    }
+}
+
+impl LearningRateSchedule for CosineAnnealing {
+    fn learning_rate(&mut self, initial: f64, epoch: usize) -> f64 {
+        // Find the position of `epoch` in its period
+        let mut period = self.period.max(1) as f64;
+        let mut position = epoch as f64;
+        while position >= period {
+            position -= period;
+            period = (period * self.period_mult).round();
+        }
+        self.min_learning_rate
+            + (initial - self.min_learning_rate) * (1. + (PI * position / period).cos()) / 2.
+    }
+    fn box_clone(&self) -> Box<dyn LearningRateSchedule> {
+        Box::new(self.clone())
+    }
+}
+
+// * Warmup
+/// Increases linearly the learning rate up to its initial value during the first `epochs` epochs,
+/// then follows `schedule`, started from its first epoch.
+#[derive(Clone, Debug)]
+pub struct Warmup {
+    pub epochs: usize,
+    pub schedule: Box<dyn LearningRateSchedule>,
+}
+
+impl Warmup {
+    pub fn new<S: LearningRateSchedule + 'static>(epochs: usize, schedule: S) -> Self {
+        Self {
+            epochs,
+            schedule: Box::new(schedule),
+        }
+    }
+}
+
+impl LearningRateSchedule for Warmup {
+    fn learning_rate(&mut self, initial: f64, epoch: usize) -> f64 {
+        if epoch < self.epochs {
+            initial * (epoch + 1) as f64 / (self.epochs + 1) as f64
+        } else {
+            self.schedule.learning_rate(initial, epoch - self.epochs)
+        }
+    }
+    fn on_epoch_end(&mut self, logs: &history::Epoch) {
+        if logs.epoch >= self.epochs {
+            self.schedule.on_epoch_end(logs);
+        }
+    }
+    fn reset(&mut self) {
+        self.schedule.reset();
+    }
+    fn monitor(&self) -> Option<&Monitor> {
+        self.schedule.monitor()
+    }
+    fn box_clone(&self) -> Box<dyn LearningRateSchedule> {
+        Box::new(self.clone())
+    }
+}
+
+// * One cycle
+/// Increases the learning rate from `initial / div_factor` to its initial value during the first
+/// `pct_start` part of the `epochs` epochs, then decreases it to `initial / final_div_factor`,
+/// both along a half cosine.\
+/// The learning rate of the network is then the maximal one.
+#[derive(Clone, Debug)]
+pub struct OneCycle {
+    pub epochs: usize,
+    pub pct_start: f64,
+    pub div_factor: f64,
+    pub final_div_factor: f64,
+}
+
+impl OneCycle {
+    /// Returns a new `OneCycle` over `epochs` epochs, with `pct_start = 0.3`, `div_factor = 25`
+    /// and `final_div_factor = 1e4`.
+    pub fn new(epochs: usize) -> Self {
+        Self {
+            epochs,
+            pct_start: 0.3,
+            div_factor: 25.,
+            final_div_factor: 1e4,
+        }
+    }
+}
+
+impl LearningRateSchedule for OneCycle {
+    fn learning_rate(&mut self, initial: f64, epoch: usize) -> f64 {
+        let last = self.epochs.max(2) as f64 - 1.;
+        let peak = (self.pct_start * last).round().max(1.);
+        let epoch = (epoch as f64).min(last);
+        // Half cosine from `from` to `to`, at the proportion `t`
+        let anneal = |from: f64, to: f64, t: f64| to + (from - to) * (1. + (PI * t).cos()) / 2.;
+
+        if epoch <= peak {
+            anneal(initial / self.div_factor, initial, epoch / peak)
+        } else {
+            let end = initial / self.final_div_factor;
+            anneal(initial, end, (epoch - peak) / (last - peak).max(1.))
+        }
+    }
+    fn box_clone(&self) -> Box<dyn LearningRateSchedule> {
+        Box::new(self.clone())
+    }
+}
+
+// * Reduce on plateau
+/// Multiplies the learning rate by `factor` when the monitored loss did not improve
+/// by more than `min_delta` for `patience` epochs, down to `min_learning_rate`.\
+/// It monitors the testing loss by default (`fit` then needs testing datas) :
+/// epochs where the monitored value was not recorded are ignored.
+#[derive(Clone, Debug)]
+pub struct ReduceOnPlateau {
+    pub monitor: Monitor,
+    pub factor: f64,
+    pub patience: usize,
+    pub min_delta: f64,
+    pub min_learning_rate: f64,
+    scale: f64,
+    plateau: Plateau,
+}
+
+impl ReduceOnPlateau {
+    /// Returns a new `ReduceOnPlateau` monitoring the testing loss,
+    /// with `min_delta = 0` and `min_learning_rate = 0`.
+    pub fn new(factor: f64, patience: usize) -> Self {
+        Self {
+            monitor: Monitor::TestLoss,
+            factor,
+            patience,
+            min_delta: 0.,
+            min_learning_rate: 0.,
+            scale: 1.,
+            plateau: Default::default(),
+        }
+    }
+
+    /// Monitor another quantity than the testing loss.
+    pub fn with_monitor(mut self, monitor: Monitor) -> Self {
+        self.monitor = monitor;
+        self
+    }
+
+    /// Only count as improvements changes greater than `min_delta`.
+    pub fn with_min_delta(mut self, min_delta: f64) -> Self {
+        self.min_delta = min_delta.abs();
+        self
+    }
+
+    /// Never reduce the learning rate below `min_learning_rate`.
+    pub fn with_min_learning_rate(mut self, min_learning_rate: f64) -> Self {
+        self.min_learning_rate = min_learning_rate;
+        self
+    }
+}
+
+impl LearningRateSchedule for ReduceOnPlateau {
+    fn learning_rate(&mut self, initial: f64, _epoch: usize) -> f64 {
+        (initial * self.scale).max(self.min_learning_rate)
+    }
+    fn on_epoch_end(&mut self, logs: &history::Epoch) {
+        let mode = self.monitor.mode();
+        let improved = self
+            .plateau
+            .update(&self.monitor, mode, self.min_delta, logs);
+        if improved == Some(false) && self.plateau.wait >= self.patience {
+            self.scale *= self.factor;
+            self.plateau.wait = 0;
+        }
+    }
+    fn reset(&mut self) {
+        self.scale = 1.;
+        self.plateau = Default::default();
+    }
+    fn monitor(&self) -> Option<&Monitor> {
+        Some(&self.monitor)
+    }
+    fn box_clone(&self) -> Box<dyn LearningRateSchedule> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,413 @@
+//! ### Serialization
+//! Provides `NNetwork::save` and `NNetwork::load`, to persist a network to a binary file.
+//!
+//! #### File format
+//! Every number is written in little-endian, strings are written as their length (`u64`)
+//! followed by their UTF-8 bytes, and matrices as their number of rows (`u64`),
+//! of columns (`u64`), then their values (`f64`) row by row.
+//!
+//! | Field          | Type                                        |
+//! |----------------|---------------------------------------------|
+//! | magic number   | 4 bytes, `SPTZ`                             |
+//! | format version | `u32`, currently `1`                        |
+//! | learning rate  | `f64`                                       |
+//! | epochs         | `u64`                                       |
+//! | batches        | `u64`                                       |
+//! | loss           | string name, then `f64` parameter           |
+//! | input shape    | dimensions number `u64`, then each `u64`    |
+//! | layers number  | `u64`                                       |
+//! | each layer     | name string, settings, state, parameters matrices |
+//!
+//! The settings and state of each layer are :
+//!
+//! | Layer               | Settings                                                         | State                              |
+//! |---------------------|------------------------------------------------------------------|------------------------------------|
+//! | `dense`             | size `u64`, activation string, `f64` parameter                   |                                    |
+//! | `dropout`           | dropout string, `f64` rate                                       |                                    |
+//! | `batch_norm`        | momentum and epsilon `f64`                                       | running mean and variance matrices |
+//! | `prelu`             |                                                                  |                                    |
+//! | `conv2d`            | filters `u64`, kernel, stride, padding and dilation `u64` pairs, |                                    |
+//! |                     | format string, activation string, `f64` parameter                |                                    |
+//! | `max_pool2d`        | pool and stride `u64` pairs, format string                       |                                    |
+//! | `avg_pool2d`        | pool and stride `u64` pairs, format string                       |                                    |
+//! | `global_max_pool2d` | format string                                                    |                                    |
+//! | `global_avg_pool2d` | format string                                                    |                                    |
+//!
+//! Files written with another format version are refused when loaded, and custom layers cannot be saved.\
+//! Custom activations are saved by name, and must be registered in an `ActivationRegistry` to be loaded.
+
+use crate::layers::{
+    AvgPool2D, Conv2D, DataFormat, Dense, DropoutLayer, GlobalAveragePooling2D, GlobalMaxPooling2D,
+    Layer, MaxPool2D, PRelu,
+};
+use crate::{
+    maths, ActivationRegistry, Array2, BatchNorm, Dropout, NNetwork, PrivateCalls, SpitzError,
+};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// Magic number written at the beginning of every file.
+const MAGIC: &[u8; 4] = b"SPTZ";
+/// Version of the file format, to increment on every incompatible change to a released format.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Returns an `InvalidFile` error with given message.
+fn invalid(message: String) -> SpitzError {
+    SpitzError::InvalidFile(message)
+}
+
+// * Writers
+fn write_u32<W: Write>(w: &mut W, value: u32) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_u64<W: Write>(w: &mut W, value: u64) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_f64<W: Write>(w: &mut W, value: f64) -> io::Result<()> {
+    w.write_all(&value.to_bits().to_le_bytes())
+}
+
+fn write_str<W: Write>(w: &mut W, value: &str) -> io::Result<()> {
+    write_u64(w, value.len() as u64)?;
+    w.write_all(value.as_bytes())
+}
+
+fn write_matrix<W: Write>(w: &mut W, matrix: &Array2<f64>) -> io::Result<()> {
+    write_u64(w, matrix.nrows() as u64)?;
+    write_u64(w, matrix.ncols() as u64)?;
+    for &value in matrix.iter() {
+        write_f64(w, value)?;
+    }
+    Ok(())
+}
+
+fn write_pair<W: Write>(w: &mut W, (first, second): (usize, usize)) -> io::Result<()> {
+    write_u64(w, first as u64)?;
+    write_u64(w, second as u64)
+}
+
+/// Writes the settings of a pooling layer : its pool and stride pairs, then its format.
+fn write_pooling<W: Write>(
+    w: &mut W,
+    pool: (usize, usize),
+    stride: (usize, usize),
+    format: DataFormat,
+) -> io::Result<()> {
+    write_pair(w, pool)?;
+    write_pair(w, stride)?;
+    write_str(w, format.name())
+}
+
+// * Readers
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_usize<R: Read>(r: &mut R) -> Result<usize, SpitzError> {
+    let value = read_u64(r)?;
+    if value > u32::MAX as u64 {
+        return Err(invalid(format!("size {} is too large", value)));
+    }
+    Ok(value as usize)
+}
+
+fn read_f64<R: Read>(r: &mut R) -> io::Result<f64> {
+    Ok(f64::from_bits(read_u64(r)?))
+}
+
+/// Returns an error if `count` values of `size` bytes do not fit in the rest `r` of the file,
+/// before anything is allocated for them.
+fn check_remaining(r: &[u8], count: usize, size: usize) -> Result<(), SpitzError> {
+    match count.checked_mul(size) {
+        Some(bytes) if bytes <= r.len() => Ok(()),
+        _ => Err(invalid(format!(
+            "{} values of {} bytes exceed the end of the file",
+            count, size
+        ))),
+    }
+}
+
+fn read_str(r: &mut &[u8]) -> Result<String, SpitzError> {
+    let len = read_usize(r)?;
+    check_remaining(r, len, 1)?;
+    let mut buf = vec![0; len];
+    r.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(|e| invalid(e.to_string()))
+}
+
+/// Reads a matrix of any shape : it is checked against the architecture once the network is loaded.
+fn read_matrix(r: &mut &[u8]) -> Result<Array2<f64>, SpitzError> {
+    let rows = read_usize(r)?;
+    let cols = read_usize(r)?;
+    let len = rows
+        .checked_mul(cols)
+        .ok_or_else(|| invalid(format!("matrix of shape {:?} is too large", (rows, cols))))?;
+    check_remaining(r, len, 8)?;
+    let mut values = Vec::with_capacity(len);
+    for _ in 0..len {
+        values.push(read_f64(r)?);
+    }
+    Array2::from_shape_vec((rows, cols), values).map_err(|e| invalid(e.to_string()))
+}
+
+fn read_pair<R: Read>(r: &mut R) -> Result<(usize, usize), SpitzError> {
+    Ok((read_usize(r)?, read_usize(r)?))
+}
+
+fn read_format(r: &mut &[u8]) -> Result<DataFormat, SpitzError> {
+    let format = read_str(r)?;
+    DataFormat::from_name(&format)
+        .ok_or_else(|| invalid(format!("unknown data format `{}`", format)))
+}
+
+impl NNetwork {
+    /// ## Save the network
+    /// Writes the architecture, parameters and hyperparameters of the network to the file at `path`.\
+    /// Datas and optimizer state are not saved.\
+    /// The file is only written once the whole network is serialized, so it is left untouched on errors.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::UninitializedNetwork` if the network was not initialized.\
+    /// Returns `SpitzError::UnsupportedLayer` if the network has a custom layer.\
+    /// Returns `SpitzError::Io` if the file cannot be written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SpitzError> {
+        if !self.parameters_match_architecture() {
+            return Err(SpitzError::UninitializedNetwork);
+        }
+        let mut w = Vec::new();
+
+        w.write_all(MAGIC)?;
+        write_u32(&mut w, FORMAT_VERSION)?;
+
+        // Hyperparameters
+        write_f64(&mut w, self.learning_rate)?;
+        write_u64(&mut w, self.epochs as u64)?;
+        write_u64(&mut w, self.batches as u64)?;
+        let (loss, parameter) = self.loss.name();
+        write_str(&mut w, loss)?;
+        write_f64(&mut w, parameter)?;
+
+        // Architecture and parameters
+        let layers = &self.architecture.layers;
+        let input = layers[0].input_shape();
+        write_u64(&mut w, input.len() as u64)?;
+        for &dimension in &input {
+            write_u64(&mut w, dimension as u64)?;
+        }
+        write_u64(&mut w, layers.len() as u64)?;
+        for layer in layers {
+            write_str(&mut w, layer.name())?;
+            let layer_any = layer.as_any();
+            if let Some(dense) = layer_any.downcast_ref::<Dense>() {
+                write_u64(&mut w, dense.size as u64)?;
+                let (activation, parameter) = dense.activation.name();
+                write_str(&mut w, activation)?;
+                write_f64(&mut w, parameter)?;
+            } else if let Some(dropout) = layer_any.downcast_ref::<DropoutLayer>() {
+                write_str(&mut w, dropout.dropout.name())?;
+                write_f64(&mut w, dropout.dropout.rate())?;
+            } else if let Some(batch_norm) = layer_any.downcast_ref::<BatchNorm>() {
+                write_f64(&mut w, batch_norm.momentum)?;
+                write_f64(&mut w, batch_norm.epsilon)?;
+                write_matrix(&mut w, &batch_norm.running_mean)?;
+                write_matrix(&mut w, &batch_norm.running_var)?;
+            } else if let Some(conv) = layer_any.downcast_ref::<Conv2D>() {
+                write_u64(&mut w, conv.filters as u64)?;
+                for &pair in &[conv.kernel, conv.stride, conv.padding, conv.dilation] {
+                    write_pair(&mut w, pair)?;
+                }
+                write_str(&mut w, conv.format.name())?;
+                let (activation, parameter) = conv.activation.name();
+                write_str(&mut w, activation)?;
+                write_f64(&mut w, parameter)?;
+            } else if let Some(pool) = layer_any.downcast_ref::<MaxPool2D>() {
+                write_pooling(&mut w, pool.pool, pool.stride, pool.format)?;
+            } else if let Some(pool) = layer_any.downcast_ref::<AvgPool2D>() {
+                write_pooling(&mut w, pool.pool, pool.stride, pool.format)?;
+            } else if let Some(pool) = layer_any.downcast_ref::<GlobalMaxPooling2D>() {
+                write_str(&mut w, pool.format.name())?;
+            } else if let Some(pool) = layer_any.downcast_ref::<GlobalAveragePooling2D>() {
+                write_str(&mut w, pool.format.name())?;
+            } else if layer_any.is::<PRelu>() {
+                // Its slopes are its parameters
+            } else {
+                return Err(SpitzError::UnsupportedLayer(layer.name().to_string()));
+            }
+            for parameter in layer.parameters() {
+                write_matrix(&mut w, parameter)?;
+            }
+        }
+
+        fs::write(path, w)?;
+        Ok(())
+    }
+
+    /// ## Load a network
+    /// Reads a network previously written by `NNetwork::save` from the file at `path`.\
+    /// The returned network is ready to predict ; to train it again, import datas and call `init`,
+    /// which keeps the loaded parameters the first time it is called.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::IncompatibleVersion` if the file was written with another format version.\
+    /// Returns `SpitzError::InvalidFile` if the file is not a valid network file.\
+    /// Returns `SpitzError::Io` if the file cannot be read.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<NNetwork, SpitzError> {
+        Self::load_with(path, &ActivationRegistry::new())
+    }
+
+    /// ## Load a network using custom activations
+    /// Same as `NNetwork::load`, finding the `Activation::Custom` activations used by the network in `registry`.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::InvalidFile` if an activation of the network is neither built-in nor registered,
+    /// and the errors of `NNetwork::load`.
+    pub fn load_with<P: AsRef<Path>>(
+        path: P,
+        registry: &ActivationRegistry,
+    ) -> Result<NNetwork, SpitzError> {
+        let bytes = fs::read(path)?;
+        let mut r = &bytes[..];
+
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a spitz network file".to_string()));
+        }
+        let version = read_u32(&mut r)?;
+        if version != FORMAT_VERSION {
+            return Err(SpitzError::IncompatibleVersion {
+                expected: FORMAT_VERSION,
+                found: version,
+            });
+        }
+
+        let mut network = NNetwork::new();
+
+        // Hyperparameters
+        network.learning_rate = read_f64(&mut r)?;
+        network.epochs = read_usize(&mut r)?;
+        network.batches = read_usize(&mut r)?;
+        if network.epochs == 0 || network.batches == 0 {
+            return Err(invalid("epochs and batches must not be 0".to_string()));
+        }
+        let loss = read_str(&mut r)?;
+        let parameter = read_f64(&mut r)?;
+        network.loss = maths::Loss::from_name(&loss, parameter)
+            .ok_or_else(|| invalid(format!("unknown loss `{}`", loss)))?;
+
+        // Architecture and parameters
+        let dimensions = read_usize(&mut r)?;
+        check_remaining(r, dimensions, 8)?;
+        let mut shape = Vec::with_capacity(dimensions);
+        for _ in 0..dimensions {
+            shape.push(read_usize(&mut r)?);
+        }
+        match shape
+            .iter()
+            .try_fold(1, |size: usize, &d| size.checked_mul(d))
+        {
+            Some(size) if size <= u32::MAX as usize => {}
+            _ => return Err(invalid(format!("input shape {:?} is too large", shape))),
+        }
+        if !shape.is_empty() {
+            network.architecture.input_shape(&shape);
+        }
+        let layers = read_usize(&mut r)?;
+        for _ in 0..layers {
+            let name = read_str(&mut r)?;
+            let mut layer: Box<dyn Layer> = match name.as_str() {
+                "dense" => {
+                    let size = read_usize(&mut r)?;
+                    let activation = read_str(&mut r)?;
+                    let parameter = read_f64(&mut r)?;
+                    let activation = registry
+                        .activation(&activation, parameter)
+                        .ok_or_else(|| invalid(format!("unknown activation `{}`", activation)))?;
+                    Box::new(Dense::new(size, activation))
+                }
+                "dropout" => {
+                    let dropout = read_str(&mut r)?;
+                    let rate = read_f64(&mut r)?;
+                    Box::new(DropoutLayer::new(
+                        Dropout::from_name(&dropout, rate)
+                            .ok_or_else(|| invalid(format!("unknown dropout `{}`", dropout)))?,
+                    ))
+                }
+                "batch_norm" => {
+                    let momentum = read_f64(&mut r)?;
+                    let epsilon = read_f64(&mut r)?;
+                    Box::new(
+                        BatchNorm::new()
+                            .with_momentum(momentum)
+                            .with_epsilon(epsilon),
+                    )
+                }
+                "conv2d" => {
+                    let filters = read_usize(&mut r)?;
+                    let (kernel, stride) = (read_pair(&mut r)?, read_pair(&mut r)?);
+                    let (padding, dilation) = (read_pair(&mut r)?, read_pair(&mut r)?);
+                    let format = read_format(&mut r)?;
+                    let activation = read_str(&mut r)?;
+                    let parameter = read_f64(&mut r)?;
+                    let activation = registry
+                        .activation(&activation, parameter)
+                        .ok_or_else(|| invalid(format!("unknown activation `{}`", activation)))?;
+                    Box::new(
+                        Conv2D::new(filters, kernel, activation)
+                            .with_stride(stride)
+                            .with_padding(padding)
+                            .with_dilation(dilation)
+                            .with_format(format),
+                    )
+                }
+                "max_pool2d" => {
+                    let (pool, stride) = (read_pair(&mut r)?, read_pair(&mut r)?);
+                    let format = read_format(&mut r)?;
+                    Box::new(MaxPool2D::new(pool).with_stride(stride).with_format(format))
+                }
+                "avg_pool2d" => {
+                    let (pool, stride) = (read_pair(&mut r)?, read_pair(&mut r)?);
+                    let format = read_format(&mut r)?;
+                    Box::new(AvgPool2D::new(pool).with_stride(stride).with_format(format))
+                }
+                "global_max_pool2d" => {
+                    Box::new(GlobalMaxPooling2D::new().with_format(read_format(&mut r)?))
+                }
+                "global_avg_pool2d" => {
+                    Box::new(GlobalAveragePooling2D::new().with_format(read_format(&mut r)?))
+                }
+                "prelu" => Box::new(PRelu::new()),
+                _ => return Err(invalid(format!("unknown layer `{}`", name))),
+            };
+
+            // Read the state and parameters, whose shapes are checked once every layer is built
+            if let Some(batch_norm) = layer.as_any_mut().downcast_mut::<BatchNorm>() {
+                batch_norm.running_mean = read_matrix(&mut r)?;
+                batch_norm.running_var = read_matrix(&mut r)?;
+            }
+            for parameter in layer.parameters_mut() {
+                *parameter = read_matrix(&mut r)?;
+            }
+            shape = layer.build(&shape)?;
+            network.architecture.layers.push(layer);
+        }
+        if !network.parameters_match_architecture() {
+            return Err(invalid(
+                "parameters do not match the layers sizes".to_string(),
+            ));
+        }
+        network.loaded = true;
+        Ok(network)
+    }
+}
@@ -1,64 +1,188 @@
+use crate::layers::{shape_size, BatchNorm, Dense, DropoutLayer, Layer};
 use crate::maths;
+use crate::regularization::Dropout;
+use crate::SpitzError;
 use ndarray::prelude::{array, Array2, Axis};
-
-// * Layer struct
-/// Structure describing a layer, contains : `input`, `size`, `activation`.\
-/// Mostly used internally.
-#[derive(Clone, Debug)]
-pub struct Layer {
-    pub input: usize,
-    pub size: usize,
-    pub activation: maths::TransfertFunction,
-}
-impl Layer {
-    /// Returns a new `Layer` structure with given `input`, `size` and `activation`.
-    pub fn new(input: usize, size: usize, activation: maths::Activation) -> Self {
-        Self {
-            input,
-            size,
-            activation: maths::Activation::match_activation(activation),
-        }
-    }
-}
+use ndarray_rand::rand::{seq::SliceRandom, Rng};
+use std::collections::BTreeMap;
 
 // * Architecture struct
-#[derive(Debug, Clone)]
+/// Structure describing the layers of the network.\
+/// The shapes of the layers are resolved by `build`, during `init` :
+/// the input layer size can be inferred from the training datas, and the output layer
+/// (if set with `output_layer`) gets the width of the training outputs.
+#[derive(Debug, Clone, Default)]
 pub struct Architecture {
-    pub layers: Vec<Layer>,
-    input_layer_size: Option<usize>,
+    pub layers: Vec<Box<dyn Layer>>,
+    input_shape: Option<Vec<usize>>,
+    output_activation: Option<maths::Activation>,
+    output_layer_added: bool,
 }
-impl Default for Architecture {
-    fn default() -> Self {
-        Self {
-            layers: Default::default(),
-            input_layer_size: None,
+impl Architecture {
+    pub fn add_layer(
+        &mut self,
+        neurons: usize,
+        activation: maths::Activation,
+    ) -> Result<(), SpitzError> {
+        self.add_layer_with_initializer(neurons, activation, None)
+    }
+
+    /// Adds a `Dense` layer whose weights are drawn with given `initializer`, if any.
+    pub fn add_layer_with_initializer(
+        &mut self,
+        neurons: usize,
+        activation: maths::Activation,
+        initializer: Option<maths::Initializer>,
+    ) -> Result<(), SpitzError> {
+        if neurons == 0 {
+            return Err(SpitzError::InvalidLayerSize);
         }
+        let mut layer = Dense::new(neurons, activation);
+        layer.settings.initializer = initializer;
+        self.add(Box::new(layer));
+        Ok(())
     }
-}
-impl Architecture {
-    // TODO auto-detect input and output layer size
-    pub fn add_layer(&mut self, neurons: usize, activation: maths::Activation) -> Result<(), &str> {
-        let input: usize = match self.layers.last() {
-            Some(l) => l.size,
-            None => {
-                match self.input_layer_size {
-                    Some(n) => n,
-                    None => return Err("cannot determine input layer size. Please set it with _network_.input_layer(size)")
-                }
-            },
+
+    /// Adds `layer` after the others, but before the output layer set with `output_layer`.
+    pub fn add(&mut self, layer: Box<dyn Layer>) {
+        // Keep the automatic output layer last
+        let index = if self.output_layer_added {
+            self.layers.len() - 1
+        } else {
+            self.layers.len()
         };
-        self.layers.push(Layer::new(input, neurons, activation));
+        self.layers.insert(index, layer);
+    }
+
+    /// Adds a layer applying `dropout` to the outputs of the last layer added.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::InvalidDropoutRate` if the rate of `dropout` is not in `[0, 1)`.\
+    /// Returns `SpitzError::EmptyArchitecture` if no layer was added.
+    pub fn add_dropout(&mut self, dropout: Dropout) -> Result<(), SpitzError> {
+        let rate = dropout.rate();
+        if !(0. ..1.).contains(&rate) {
+            return Err(SpitzError::InvalidDropoutRate(rate));
+        }
+        self.check_added()?;
+        self.add(Box::new(DropoutLayer::new(dropout)));
+        Ok(())
+    }
+
+    /// Adds `batch_norm`, normalizing the outputs of the last layer added.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::EmptyArchitecture` if no layer was added.
+    pub fn add_batch_norm(&mut self, batch_norm: BatchNorm) -> Result<(), SpitzError> {
+        self.check_added()?;
+        self.add(Box::new(batch_norm));
         Ok(())
     }
 
+    /// Returns `SpitzError::EmptyArchitecture` if no layer was added, apart from the output layer.
+    fn check_added(&self) -> Result<(), SpitzError> {
+        // The automatic output layer is not added with `add_layer`
+        if self.layers.len() == self.output_layer_added as usize {
+            Err(SpitzError::EmptyArchitecture)
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn input_layer(&mut self, neurons: usize) {
-        self.input_layer_size = Some(neurons);
+        self.input_shape = Some(vec![neurons]);
+    }
+
+    /// Sets the shape of the input samples, e.g. `[channels, height, width]` for images.
+    pub fn input_shape(&mut self, shape: &[usize]) {
+        self.input_shape = Some(shape.to_vec());
+    }
+
+    /// Adds an output layer whose size will be the width of the training outputs.
+    pub fn output_layer(&mut self, activation: maths::Activation) {
+        if self.output_layer_added {
+            self.layers.pop();
+            self.output_layer_added = false;
+        }
+        self.output_activation = Some(activation);
     }
+
+    /// Returns the layer at `index`, counting from the first layer added.
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::InvalidLayerIndex` if there is no such layer.
+    pub fn layer_mut(&mut self, index: usize) -> Result<&mut dyn Layer, SpitzError> {
+        let layers = self.layers.len();
+        match self.layers.get_mut(index) {
+            Some(layer) => Ok(layer.as_mut()),
+            None => Err(SpitzError::InvalidLayerIndex { index, layers }),
+        }
+    }
+
+    /// Returns the size of the input layer, if it was set.
+    pub fn input_layer_size(&self) -> Option<usize> {
+        self.input_shape.as_ref().map(|shape| shape_size(shape))
+    }
+
+    /// Returns the shape of the input samples, if it was set.
+    pub fn input_layer_shape(&self) -> Option<&[usize]> {
+        self.input_shape.as_deref()
+    }
+
+    /// Resolves the shape of every layer, from the `input` shape and the `output` size (if known).
+    ///
+    /// ### Errors
+    /// Returns `SpitzError::MissingTrainingDatas` if the output layer must be sized but `output` is unknown.\
+    /// Returns `SpitzError::EmptyArchitecture` if there is no layer.\
+    /// Returns an error if a layer cannot take the outputs of the previous one.
+    pub fn build(&mut self, input: &[usize], output: Option<usize>) -> Result<(), SpitzError> {
+        if let Some(activation) = &self.output_activation {
+            let size = output.ok_or(SpitzError::MissingTrainingDatas)?;
+            // Resize the output layer if it was already added, to keep its settings
+            let output_layer = match self.layers.last_mut() {
+                Some(layer) if self.output_layer_added => {
+                    layer.as_any_mut().downcast_mut::<Dense>()
+                }
+                _ => None,
+            };
+            match output_layer {
+                Some(layer) => layer.size = size,
+                None => {
+                    self.layers
+                        .push(Box::new(Dense::new(size, activation.clone())));
+                    self.output_layer_added = true;
+                }
+            }
+        }
+        if self.layers.is_empty() {
+            return Err(SpitzError::EmptyArchitecture);
+        }
+
+        let mut shape = input.to_vec();
+        for layer in &mut self.layers {
+            shape = layer.build(&shape)?;
+        }
+        Ok(())
+    }
+}
+
+// * Phase enum
+/// Whether the network is training or not : stochastic layers (like dropouts) only act during training.\
+/// `fit` puts the network in the `Training` phase, and back in the `Inference` phase once done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Phase {
+    Training,
+    #[default]
+    Inference,
 }
 
 // * Weights type
 pub type Weights = Vec<Array2<f64>>;
 
+// * Biases type
+/// One `(1, size)` row vector per layer, broadcasted over every sample of a batch.
+pub type Biases = Vec<Array2<f64>>;
+
 // * DatasRaw struct
 /// Structure describing training and test dataset.\
 /// To set it, use `NNetwork.import_datas`.
@@ -79,6 +203,15 @@ impl Default for DatasRaw {
         }
     }
 }
+impl DatasRaw {
+    /// Returns the training samples at given `indices`, in that order.
+    pub fn train_batch(&self, indices: &[usize]) -> DatasTrain {
+        DatasTrain {
+            x: self.train_x.select(Axis(0), indices),
+            y: self.train_y.select(Axis(0), indices),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct DatasTrain {
@@ -92,40 +225,67 @@ pub struct DatasTest {
     pub y: Array2<f64>,
 }
 
+// * Evaluation struct
+/// Result of the evaluation of a network on some datas, see `NNetwork::evaluate`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Evaluation {
+    /// Value of the loss of the network.
+    pub loss: f64,
+    /// Value of each metric set with `set_metrics`, by name.
+    pub metrics: BTreeMap<String, f64>,
+}
+
+// * Gradient check struct
+/// Result of `NNetwork::gradient_check` for one parameter of a layer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GradientCheck {
+    /// Index of the layer in the architecture.
+    pub layer: usize,
+    /// Index of the parameter in the `parameters` of the layer.
+    pub parameter: usize,
+    /// Relative error `‖a - n‖ / (‖a‖ + ‖n‖)` between the backpropagated gradient `a`
+    /// and the numerical one `n` (`0` if both are null).
+    pub relative_error: f64,
+}
+
 // * Datas struct
-/// Structure similar to `DatasRaw` but that divides dataset for each epoch.\
-/// Is used during training.
+/// Structure used during training : it keeps the order in which the training samples
+/// of `DatasRaw` are visited, and the testing datas.\
+/// Mini-batches are built lazily from `DatasRaw` with this order, see `DatasRaw::train_batch`.
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct Datas {
-    pub train: Vec<DatasTrain>,
+    /// Indices of the training samples, in the order of the current epoch.
+    pub order: Vec<usize>,
     pub test: DatasTest,
 }
 
 impl Datas {
-    /// Divides datas for each epoch.
-    pub fn from_datas_raw(&mut self, datas_raw: &DatasRaw, epochs: usize) {
+    /// Sets the testing datas, and the training samples order to the order of `datas_raw`.
+    pub fn from_datas_raw(&mut self, datas_raw: &DatasRaw) {
         self.test.x = datas_raw.test_x.clone();
         self.test.y = datas_raw.test_y.clone();
 
-        // train_x
-        let mut tr_x = Vec::new();
-        for element in datas_raw.train_x.axis_chunks_iter(Axis(0), epochs) {
-            tr_x.push(element.to_owned());
-        }
+        let samples = if datas_raw.train_x.is_empty() {
+            0
+        } else {
+            datas_raw.train_x.nrows()
+        };
+        self.order = (0..samples).collect();
+    }
 
-        // train_y
-        let mut tr_y = Vec::new();
-        for element in datas_raw.train_y.axis_chunks_iter(Axis(0), epochs) {
-            tr_y.push(element.to_owned());
-        }
+    /// Shuffles the order of the training samples with given `rng`.
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.order.shuffle(rng);
+    }
 
-        let mut id = 0;
-        while id < tr_x.len() {
-            self.train.push(DatasTrain {
-                x: tr_x[id].clone(),
-                y: tr_y[id].clone(),
-            });
-            id += 1;
-        }
+    /// Returns the indices of the training samples of each mini-batch of `batch_size` samples,
+    /// following the current order.\
+    /// If `drop_last` is set, the last batch is dropped when it is incomplete.
+    pub fn batches(&self, batch_size: usize, drop_last: bool) -> Vec<Vec<usize>> {
+        self.order
+            .chunks(batch_size.max(1))
+            .filter(|batch| !drop_last || batch.len() == batch_size)
+            .map(|batch| batch.to_vec())
+            .collect()
     }
 }
@@ -1,6 +1,8 @@
 extern crate simple_logger;
 use ndarray::prelude::*;
+use ndarray_rand::rand::{rngs::StdRng, SeedableRng};
 use spitz::*;
+use std::sync::Arc;
 
 #[test]
 fn add_layer() {
@@ -9,8 +11,11 @@ fn add_layer() {
     network
         .input_layer(10)
         .add_layer(50, Activation::Relu)
+        .unwrap()
         .add_layer(40, Activation::Sigmoid)
-        .init();
+        .unwrap()
+        .init()
+        .unwrap();
 
     for layer in network.get_architecture().layers {
         println!("{:?}", layer);
@@ -24,14 +29,140 @@ fn init_weights() {
     network
         .input_layer(10)
         .add_layer(40, Activation::Relu)
+        .unwrap()
         .add_layer(5, Activation::Sigmoid)
-        .init();
+        .unwrap()
+        .init()
+        .unwrap();
     let weights = network.get_weights();
 
     assert_eq!(weights[0].shape(), &[10, 40]);
     assert_eq!(weights[1].shape(), &[40, 5]);
 }
 
+#[test]
+fn init_biases() {
+    let mut network = NNetwork::new();
+
+    network
+        .input_layer(10)
+        .add_layer(40, Activation::Relu)
+        .unwrap()
+        .add_layer(5, Activation::Sigmoid)
+        .unwrap()
+        .init()
+        .unwrap();
+    let biases = network.get_biases();
+
+    assert_eq!(biases[0], Array2::<f64>::zeros((1, 40)));
+    assert_eq!(biases[1], Array2::<f64>::zeros((1, 5)));
+}
+
+#[test]
+fn fit_updates_biases() {
+    let x = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[1.], [3.], [5.], [7.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .input_layer(1)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(1)
+        .unwrap()
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+
+    assert_ne!(network.get_biases()[0], Array2::<f64>::zeros((1, 1)));
+}
+
+#[test]
+fn predict() {
+    let x = &array![[0., 1., 2.], [3., 4., 5.]];
+    let mut network = NNetwork::new();
+    network
+        .input_layer(3)
+        .add_layer(4, Activation::Relu)
+        .unwrap()
+        .add_layer(2, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+
+    let prediction = network.predict(x).unwrap();
+    assert_eq!(&prediction, network.feed_forward(x).last().unwrap());
+    assert_eq!(network.predict_classes(x).unwrap().len(), 2);
+    for row in network.predict_proba(x).unwrap().genrows() {
+        assert!((row.sum() - 1.).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn predict_proba_single_output() {
+    let x = &array![[0., 1., 2.], [3., 4., 5.]];
+    let mut network = NNetwork::new();
+    network
+        .input_layer(3)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+
+    // A single linear output is a logit, turned into a probability with a sigmoid
+    let prediction = network.predict(x).unwrap();
+    let proba = network.predict_proba(x).unwrap();
+    assert_eq!(proba, prediction.mapv(|v| 1. / (1. + (-v).exp())));
+    assert!(proba.iter().all(|&p| p > 0. && p < 1.));
+}
+
+#[test]
+fn predict_wrong_width() {
+    let mut network = NNetwork::new();
+    network
+        .input_layer(3)
+        .add_layer(2, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+
+    match network.predict(&array![[0., 1.]]) {
+        Err(SpitzError::IncompatibleDataWidth {
+            expected: 3,
+            found: 2,
+            ..
+        }) => (),
+        other => panic!("unexpected result : {:?}", other),
+    }
+}
+
+#[test]
+fn layer_errors() {
+    let mut network = NNetwork::new();
+    assert!(matches!(
+        network.add_layer(0, Activation::Linear),
+        Err(SpitzError::InvalidLayerSize)
+    ));
+    assert!(matches!(network.init(), Err(SpitzError::MissingInputLayer)));
+
+    network.input_layer(3);
+    assert!(matches!(network.init(), Err(SpitzError::EmptyArchitecture)));
+    assert!(matches!(
+        network.fit(),
+        Err(SpitzError::UninitializedNetwork)
+    ));
+
+    network.add_layer(2, Activation::Linear).unwrap();
+    network.init().unwrap();
+    assert!(matches!(
+        network.fit(),
+        Err(SpitzError::MissingTrainingDatas)
+    ));
+}
+
 #[test]
 #[allow(clippy::float_cmp)]
 fn set_learning_rate() {
@@ -45,7 +176,12 @@ fn set_learning_rate() {
 fn set_epochs() {
     let mut network = NNetwork::new();
 
-    network.set_epochs(25);
+    network.set_epochs(25).unwrap();
+    assert_eq!(network.epochs, 25);
+    assert!(matches!(
+        network.set_epochs(0),
+        Err(SpitzError::InvalidEpochs)
+    ));
     assert_eq!(network.epochs, 25);
 }
 
@@ -53,7 +189,12 @@ fn set_epochs() {
 fn set_batches() {
     let mut network = NNetwork::new();
 
-    network.set_batches(25);
+    network.set_batches(25).unwrap();
+    assert_eq!(network.batches, 25);
+    assert!(matches!(
+        network.set_batches(0),
+        Err(SpitzError::InvalidBatchSize)
+    ));
     assert_eq!(network.batches, 25);
 }
 
@@ -69,3 +210,309 @@ fn test_relu() {
         maths::activations::relu(x, true)
     );
 }
+
+#[test]
+fn test_sigmoid() {
+    let x: Array2<f64> = array![[0., 2., -1000.]];
+    let s = maths::activations::sigmoid(x.clone(), false);
+    assert_eq!(s[[0, 0]], 0.5);
+    assert!((s[[0, 1]] - 0.880_797_077_977_882_4).abs() < 1e-12);
+    assert_eq!(s[[0, 2]], 0.);
+
+    let d = maths::activations::sigmoid(x, true);
+    assert_eq!(d[[0, 0]], 0.25);
+    assert!((d[[0, 1]] - 0.104_993_585_403_507_1).abs() < 1e-12);
+}
+
+#[test]
+fn test_activations() {
+    let x: Array2<f64> = array![[-3., -1., -0.2], [0.3, 1., 4.]];
+    assert_eq!(
+        array![[-1.5, -0.5, -0.1], [0.3, 1., 4.]],
+        maths::activations::leaky_relu(x.clone(), false, 0.5)
+    );
+    let selu = maths::activations::selu(x.clone(), false);
+    assert!((selu[[1, 1]] - 1.0507).abs() < 1e-4);
+    assert!((selu[[0, 0]] + 1.6706).abs() < 1e-4);
+    let softplus = maths::activations::softplus(array![[-1000., 0., 1000.]], false);
+    assert_eq!(array![[0., 2f64.ln(), 1000.]], softplus);
+
+    // Every derivative matches the slope of its function
+    let eps = 1e-6;
+    for activation in &[
+        Activation::Relu,
+        Activation::Sigmoid,
+        Activation::Linear,
+        Activation::Tanh,
+        Activation::LeakyRelu(0.01),
+        Activation::Elu(1.5),
+        Activation::Selu,
+        Activation::Gelu,
+        Activation::Swish,
+        Activation::Mish,
+        Activation::Softplus,
+    ] {
+        let slope = (activation.compute(&x + eps, false) - activation.compute(&x - eps, false))
+            / (2. * eps);
+        let derivative = activation.compute(x.clone(), true);
+        for (s, d) in slope.iter().zip(derivative.iter()) {
+            assert!((s - d).abs() < 1e-6, "{:?} : {} != {}", activation, s, d);
+        }
+    }
+
+    // Only the activations without parameter are plain transfert functions
+    let relu: maths::TransfertFunction = Activation::match_activation(Activation::Relu).unwrap();
+    assert_eq!(relu(x.clone(), false), Activation::Relu.compute(x, false));
+    assert!(Activation::match_activation(Activation::LeakyRelu(0.01)).is_none());
+}
+
+/// `f(x) = x³`
+#[derive(Debug)]
+struct Cube;
+
+impl ActivationFunction for Cube {
+    fn name(&self) -> &str {
+        "cube"
+    }
+    fn forward(&self, x: Array2<f64>) -> Array2<f64> {
+        x.mapv(|x| x.powi(3))
+    }
+    fn derivative(&self, x: Array2<f64>) -> Array2<f64> {
+        x.mapv(|x| 3. * x.powi(2))
+    }
+}
+
+#[test]
+fn custom_activation() {
+    let cube = Activation::Custom(Arc::new(Cube));
+    let x = array![[-2., 0., 1.]];
+    assert_eq!(cube.compute(x.clone(), false), array![[-8., 0., 1.]]);
+    assert_eq!(cube.compute(x, true), array![[12., 0., 3.]]);
+    assert!(Activation::match_activation(cube.clone()).is_none());
+    assert_eq!(cube.name(), ("cube", 0.));
+    assert_eq!(cube, Activation::Custom(Arc::new(Cube)));
+
+    let mut registry = ActivationRegistry::new();
+    registry.register(Arc::new(Cube));
+    assert_eq!(registry.activation("cube", 0.), Some(cube));
+    assert_eq!(registry.activation("elu", 1.), Some(Activation::Elu(1.)));
+    assert_eq!(registry.activation("square", 0.), None);
+}
+
+#[test]
+fn test_softmax() {
+    let x: Array2<f64> = array![[1., 2., 3.], [1000., 1000., 1000.]];
+    let s = maths::activations::softmax(x, false);
+
+    for row in s.genrows() {
+        assert!((row.sum() - 1.).abs() < 1e-12);
+    }
+    assert!(s[[0, 0]] < s[[0, 1]] && s[[0, 1]] < s[[0, 2]]);
+    assert!((s[[1, 0]] - 1. / 3.).abs() < 1e-12);
+
+    // The jacobian of the softmax removes any constant added to the gradient
+    let grad = array![[1., 1., 1.], [0., 0., 0.]];
+    let back = maths::activations::softmax_backward(&s, &grad);
+    assert!(back.iter().all(|v| v.abs() < 1e-12));
+}
+
+#[test]
+fn test_losses() {
+    let y_pred: Array2<f64> = array![[0.5, 2.], [1., -1.]];
+    let y: Array2<f64> = array![[1., 0.], [1., 1.]];
+
+    assert!((Loss::MeanSquaredError.loss(&y_pred, &y) - 2.0625).abs() < 1e-12);
+    assert!((Loss::MeanAbsoluteError.loss(&y_pred, &y) - 1.125).abs() < 1e-12);
+    assert!((Loss::Huber(1.).loss(&y_pred, &y) - 0.78125).abs() < 1e-12);
+    assert_eq!(
+        array![[-0.25, 1.], [0., -1.]],
+        Loss::MeanSquaredError.derivative(&y_pred, &y)
+    );
+    assert_eq!(
+        array![[-0.125, 0.25], [0., -0.25]],
+        Loss::Huber(1.).derivative(&y_pred, &y)
+    );
+
+    let p: Array2<f64> = array![[0.25, 0.75], [0.5, 0.5]];
+    let t: Array2<f64> = array![[0., 1.], [1., 0.]];
+    let expected = -(0.75f64.ln() + 0.5f64.ln()) / 2.;
+    assert!((Loss::CategoricalCrossEntropy.loss(&p, &t) - expected).abs() < 1e-12);
+}
+
+#[test]
+fn initializers() {
+    let rng = &mut StdRng::seed_from_u64(0);
+    let limit = (6.0_f64 / 50.).sqrt();
+    let w = Initializer::GlorotUniform.weights(20, 30, rng).unwrap();
+    assert_eq!(w.shape(), &[20, 30]);
+    assert!(w.iter().all(|v| v.abs() <= limit));
+
+    let w = Initializer::HeNormal.weights(200, 100, rng).unwrap();
+    let variance = w.mapv(|v| v * v).mean().unwrap();
+    assert!((variance - 2. / 200.).abs() < 0.002);
+
+    // Orthogonal : columns (resp. rows) are orthonormal
+    let w = Initializer::Orthogonal(1.).weights(8, 4, rng).unwrap();
+    assert!((w.t().dot(&w) - Array2::<f64>::eye(4))
+        .iter()
+        .all(|v| v.abs() < 1e-9));
+    let w = Initializer::Orthogonal(2.).weights(3, 6, rng).unwrap();
+    assert!((w.dot(&w.t()) - Array2::<f64>::eye(3) * 4.)
+        .iter()
+        .all(|v| v.abs() < 1e-9));
+
+    assert_eq!(
+        Initializer::for_activation(&Activation::Relu),
+        Initializer::HeNormal
+    );
+}
+
+#[test]
+fn set_initializers() {
+    let mut network = NNetwork::new();
+    network
+        .input_layer(4)
+        .set_initializer(Initializer::Uniform(1., 2.))
+        .add_layer(3, Activation::Relu)
+        .unwrap()
+        .add_layer_with_initializer(2, Activation::Sigmoid, Initializer::Uniform(-2., -1.))
+        .unwrap()
+        .init()
+        .unwrap();
+
+    let weights = network.get_weights();
+    assert!(weights[0].iter().all(|v| (1.0..=2.).contains(v)));
+    assert!(weights[1].iter().all(|v| (-2.0..=-1.).contains(v)));
+}
+
+#[test]
+fn invalid_initializers() {
+    let rng = &mut StdRng::seed_from_u64(0);
+    for initializer in &[
+        Initializer::Uniform(1., 0.),
+        Initializer::Uniform(f64::NAN, 1.),
+        Initializer::Orthogonal(f64::INFINITY),
+    ] {
+        assert!(matches!(
+            initializer.weights(2, 2, rng),
+            Err(SpitzError::InvalidInitializer(_))
+        ));
+    }
+
+    // `init` returns the error instead of panicking
+    let mut network = NNetwork::new();
+    network
+        .input_layer(4)
+        .set_initializer(Initializer::Uniform(1., 0.))
+        .add_layer(3, Activation::Relu)
+        .unwrap();
+    assert!(matches!(
+        network.init(),
+        Err(SpitzError::InvalidInitializer(_))
+    ));
+}
+
+#[test]
+fn set_seed() {
+    let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
+    let y = &array![[0.], [1.], [1.], [0.]];
+
+    let train = |seed| {
+        let mut network = NNetwork::new();
+        network
+            .import_train_datas(x, y)
+            .unwrap()
+            .set_seed(seed)
+            .add_layer(4, Activation::Relu)
+            .unwrap()
+            .output_layer(Activation::Sigmoid)
+            .set_batches(1)
+            .unwrap()
+            .init()
+            .unwrap()
+            .fit()
+            .unwrap();
+        network.get_weights()
+    };
+
+    assert_eq!(train(42), train(42));
+    assert_ne!(train(42), train(43));
+}
+
+#[test]
+fn test_metrics() {
+    let y_pred = &array![[0.9], [0.2], [0.7], [0.4]];
+    let y = &array![[1.], [0.], [0.], [1.]];
+
+    assert_eq!(Metric::Accuracy.compute(y_pred, y), 0.5);
+    assert_eq!(Metric::Precision.compute(y_pred, y), 0.5);
+    assert_eq!(Metric::Recall.compute(y_pred, y), 0.5);
+    assert_eq!(Metric::F1.compute(y_pred, y), 0.5);
+
+    // Multiple classes are averaged
+    let y_pred = &array![[0.8, 0.1, 0.1], [0.2, 0.7, 0.1], [0.6, 0.3, 0.1]];
+    let y = &array![[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+    assert!((Metric::Accuracy.compute(y_pred, y) - 2. / 3.).abs() < 1e-12);
+    assert!((Metric::Precision.compute(y_pred, y) - 0.5).abs() < 1e-12);
+    assert!((Metric::Recall.compute(y_pred, y) - 2. / 3.).abs() < 1e-12);
+
+    let y_pred = &array![[1.], [2.], [4.]];
+    let y = &array![[1.], [3.], [5.]];
+    assert!((Metric::MeanAbsoluteError.compute(y_pred, y) - 2. / 3.).abs() < 1e-12);
+    assert!((Metric::RootMeanSquaredError.compute(y_pred, y) - (2f64 / 3.).sqrt()).abs() < 1e-12);
+    assert!((Metric::R2.compute(y_pred, y) - (1. - 2. / 8.)).abs() < 1e-12);
+    assert_eq!(Metric::R2.compute(y, y), 1.);
+}
+
+#[test]
+fn evaluate() {
+    let x = &array![[0.], [1.], [2.], [3.], [4.], [5.]];
+    let y = &array![[1.], [3.], [5.], [7.], [9.], [11.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_metrics(vec![Metric::MeanAbsoluteError, Metric::R2])
+        .init()
+        .unwrap();
+    assert!(matches!(
+        network.evaluate(),
+        Err(SpitzError::MissingTestDatas)
+    ));
+
+    network
+        .import_test_datas(&array![[6.], [7.]], &array![[13.], [15.]])
+        .unwrap()
+        .set_evaluate_each_epoch(true)
+        .set_epochs(50)
+        .unwrap()
+        .set_batches(1)
+        .unwrap()
+        .set_learning_rate(0.01)
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+
+    let evaluation = network.evaluate().unwrap();
+    assert_eq!(evaluation.metrics.len(), 2);
+    assert!(evaluation.metrics["r2"] > 0.9);
+    assert_eq!(
+        network.get_history().metric("mae").last().unwrap().unwrap(),
+        evaluation.metrics["mae"]
+    );
+
+    let on_train = network.evaluate_on(x, y).unwrap();
+    assert!(on_train.loss < 1.);
+    assert!(matches!(
+        network.evaluate_on(x, &Array2::zeros((6, 2))),
+        Err(SpitzError::IncompatibleDataWidth { .. })
+    ));
+    assert!(matches!(
+        network.evaluate_on(x, &array![[1.]]),
+        Err(SpitzError::ShapeMismatch { x: 6, y: 1 })
+    ));
+}
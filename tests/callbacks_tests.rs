@@ -0,0 +1,277 @@
+use ndarray::prelude::*;
+use spitz::history::Epoch;
+use spitz::*;
+use std::sync::{Arc, Mutex};
+
+/// Records the hooks it gets, and stops the training after `stop_after` batches.
+#[derive(Clone)]
+struct Recorder {
+    events: Arc<Mutex<Vec<String>>>,
+    stop_after: usize,
+    batches: usize,
+}
+
+impl Callback for Recorder {
+    fn on_train_begin(&mut self, network: &NNetwork) -> Control {
+        assert_eq!(network.get_weights().len(), 1);
+        self.events
+            .lock()
+            .unwrap()
+            .push(String::from("train_begin"));
+        Control::Continue
+    }
+    fn on_epoch_begin(&mut self, _network: &NNetwork, epoch: usize) -> Control {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("epoch_begin {}", epoch));
+        Control::Continue
+    }
+    fn on_batch_end(
+        &mut self,
+        _network: &NNetwork,
+        epoch: usize,
+        batch: usize,
+        loss: f64,
+    ) -> Control {
+        assert!(loss.is_finite());
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("batch_end {} {}", epoch, batch));
+        self.batches += 1;
+        if self.batches == self.stop_after {
+            Control::Stop
+        } else {
+            Control::Continue
+        }
+    }
+    fn on_epoch_end(&mut self, _network: &NNetwork, logs: &Epoch) -> Control {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("epoch_end {}", logs.epoch));
+        Control::Continue
+    }
+    fn on_train_end(&mut self, _network: &NNetwork) {
+        self.events.lock().unwrap().push(String::from("train_end"));
+    }
+    fn box_clone(&self) -> Box<dyn Callback> {
+        Box::new(self.clone())
+    }
+}
+
+fn network(events: &Arc<Mutex<Vec<String>>>, stop_after: usize) -> NNetwork {
+    let x = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[1.], [3.], [5.], [7.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(2)
+        .unwrap()
+        .set_batches(2)
+        .unwrap()
+        .add_callback(Recorder {
+            events: events.clone(),
+            stop_after,
+            batches: 0,
+        })
+        .init()
+        .unwrap();
+    network
+}
+
+#[test]
+fn callbacks_hooks() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    network(&events, 0).fit().unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            "train_begin",
+            "epoch_begin 0",
+            "batch_end 0 0",
+            "batch_end 0 1",
+            "epoch_end 0",
+            "epoch_begin 1",
+            "batch_end 1 0",
+            "batch_end 1 1",
+            "epoch_end 1",
+            "train_end",
+        ]
+    );
+}
+
+#[test]
+fn callbacks_stop() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut network = network(&events, 3);
+    network.fit().unwrap();
+
+    assert_eq!(
+        events.lock().unwrap()[5..],
+        ["epoch_begin 1", "batch_end 1 0", "epoch_end 1", "train_end"]
+    );
+    assert_eq!(network.get_history().epochs.len(), 2);
+}
+
+/// Keeps the weights of the first epoch.
+#[derive(Clone, Default)]
+struct FirstWeights(Arc<Mutex<Option<Weights>>>);
+
+impl Callback for FirstWeights {
+    fn on_epoch_end(&mut self, network: &NNetwork, logs: &Epoch) -> Control {
+        if logs.epoch == 0 {
+            *self.0.lock().unwrap() = Some(network.get_weights());
+        }
+        Control::Continue
+    }
+    fn box_clone(&self) -> Box<dyn Callback> {
+        Box::new(self.clone())
+    }
+}
+
+#[test]
+fn early_stopping() {
+    let x = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[1.], [3.], [5.], [7.]];
+
+    // Without learning, the loss never improves after the first epoch
+    // (apart from rounding errors, as the datas are shuffled)
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(50)
+        .unwrap()
+        .set_learning_rate(0.)
+        .set_early_stopping(EarlyStopping::new(Monitor::TrainLoss, 3).with_min_delta(1e-9))
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+    assert_eq!(network.get_history().epochs.len(), 4);
+
+    // Monitoring the testing datas needs some
+    network.set_early_stopping(EarlyStopping::new(Monitor::TestLoss, 3));
+    assert!(matches!(network.fit(), Err(SpitzError::MissingTestDatas)));
+
+    // Monitored metrics are evaluated, and maximized if needed
+    let early_stopping = EarlyStopping::new(Monitor::Metric(Metric::R2), 2);
+    assert_eq!(early_stopping.mode, early_stopping::Mode::Max);
+    network
+        .import_test_datas(x, y)
+        .unwrap()
+        .set_early_stopping(early_stopping)
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+    assert_eq!(network.metrics, vec![Metric::R2]);
+    assert_eq!(network.get_history().epochs.len(), 3);
+    assert!(network.get_history().metric("r2")[0].is_some());
+}
+
+#[test]
+fn early_stopping_restore() {
+    let x = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[1.], [3.], [5.], [7.]];
+    let first = FirstWeights::default();
+
+    // A too high learning rate makes the loss diverge, so the first epoch is the best
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(50)
+        .unwrap()
+        .set_batches(4)
+        .unwrap()
+        .set_learning_rate(2.)
+        .add_callback(first.clone())
+        .set_early_stopping(EarlyStopping::new(Monitor::TrainLoss, 2).with_restore_best_weights())
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+
+    let history = network.get_history();
+    assert_eq!(history.epochs.len(), 3);
+    assert!(history.train_loss()[2] > history.train_loss()[0]);
+    assert_eq!(
+        network.early_stopping.as_ref().unwrap().best_epoch(),
+        Some(0)
+    );
+    assert_eq!(Some(network.get_weights()), *first.0.lock().unwrap());
+}
+
+/// Keeps the batch normalization layer of each epoch.
+#[derive(Clone, Default)]
+struct BatchNorms(Arc<Mutex<Vec<BatchNorm>>>);
+
+impl Callback for BatchNorms {
+    fn on_epoch_end(&mut self, network: &NNetwork, _logs: &Epoch) -> Control {
+        let layers = network.get_architecture().layers;
+        let batch_norm = layers[1].as_any().downcast_ref::<BatchNorm>().unwrap();
+        self.0.lock().unwrap().push(batch_norm.clone());
+        Control::Continue
+    }
+    fn box_clone(&self) -> Box<dyn Callback> {
+        Box::new(self.clone())
+    }
+}
+
+#[test]
+fn early_stopping_restore_batch_norm() {
+    let x = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[1.], [3.], [5.], [7.]];
+    let batch_norms = BatchNorms::default();
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .set_seed(1)
+        .add_layer(4, Activation::Linear)
+        .unwrap()
+        .add_batch_norm(BatchNorm::new().with_momentum(0.5))
+        .unwrap()
+        .output_layer(Activation::Linear)
+        .set_epochs(50)
+        .unwrap()
+        .set_batches(2)
+        .unwrap()
+        .set_learning_rate(2.)
+        .add_callback(batch_norms.clone())
+        .set_early_stopping(EarlyStopping::new(Monitor::TrainLoss, 2).with_restore_best_weights())
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+
+    // The scales, shifts and running statistics are those of the best epoch, not of the last one
+    let epochs = network.get_history().epochs.len();
+    let best = network
+        .early_stopping
+        .as_ref()
+        .unwrap()
+        .best_epoch()
+        .unwrap();
+    assert!(best + 2 < epochs);
+    let layers = network.get_architecture().layers;
+    let restored = layers[1].as_any().downcast_ref::<BatchNorm>().unwrap();
+    let batch_norms = batch_norms.0.lock().unwrap();
+    assert_eq!(restored.gamma, batch_norms[best].gamma);
+    assert_eq!(restored.running_mean, batch_norms[best].running_mean);
+    assert_eq!(restored.running_var, batch_norms[best].running_var);
+    assert_ne!(restored.running_mean, batch_norms[epochs - 1].running_mean);
+}
@@ -1,5 +1,6 @@
 extern crate simple_logger;
 use ndarray::prelude::*;
+use ndarray_rand::rand::{rngs::StdRng, SeedableRng};
 use spitz::*;
 
 #[test]
@@ -8,17 +9,23 @@ fn import_datas() {
     let y = &array![[0.], [1.], [2.], [3.]];
 
     let mut network = NNetwork::new();
-    network.import_datas(x, y, 0.25).init();
-
-    let mut wanted_datas_train: Vec<DatasTrain> = Vec::new();
-    wanted_datas_train.push(DatasTrain {
-        x: array![[0., 1., 2.], [3., 4., 5.], [6., 7., 8.]],
-        y: array![[0.], [1.], [2.]],
-    });
+    network
+        .import_datas(x, y, 0.25)
+        .unwrap()
+        .input_layer(3)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
 
     assert_eq!(network.datas.test.x, array![[9., 10., 11.]]);
     assert_eq!(network.datas.test.y, array![[3.]]);
-    assert_eq!(network.datas.train, wanted_datas_train);
+    assert_eq!(
+        network.datas_raw.train_x,
+        array![[0., 1., 2.], [3., 4., 5.], [6., 7., 8.]]
+    );
+    assert_eq!(network.datas_raw.train_y, array![[0.], [1.], [2.]]);
+    assert_eq!(network.datas.order, vec![0, 1, 2]);
 }
 
 #[test]
@@ -27,45 +34,227 @@ fn import_datas_no_test() {
     let y = &array![[0.], [1.], [2.], [3.]];
 
     let mut network = NNetwork::new();
-    network.import_train_datas(x, y).init();
-
-    let mut wanted_datas_train: Vec<DatasTrain> = Vec::new();
-    wanted_datas_train.push(DatasTrain {
-        x: array![[0., 1., 2.], [1., 2., 3.], [2., 3., 4.], [3., 4., 5.]],
-        y: array![[0.], [1.], [2.], [3.]],
-    });
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .input_layer(3)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
 
     // TODO verify which shape should be used if there is no test data for `Datas.test.{x, y}`
     //assert_eq!(network.datas.test.x, array![[]]);
     //assert_eq!(network.datas.test.y, array![[]]);
-    assert_eq!(network.datas.train, wanted_datas_train);
+    assert_eq!(&network.datas_raw.train_x, x);
+    assert_eq!(network.datas.order, vec![0, 1, 2, 3]);
 }
 
 #[test]
 fn import_datas_batches() {
+    let x = &array![
+        [0., 1., 2.],
+        [1., 2., 3.],
+        [2., 3., 4.],
+        [3., 4., 5.],
+        [4., 5., 6.]
+    ];
+    let y = &array![[0.], [1.], [2.], [3.], [4.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .input_layer(3)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+
+    assert_eq!(
+        network.datas.batches(2, false),
+        vec![vec![0, 1], vec![2, 3], vec![4]]
+    );
+    assert_eq!(network.datas.batches(2, true), vec![vec![0, 1], vec![2, 3]]);
+
+    // Batches are built from the raw datas, in the given order
+    assert_eq!(
+        network.datas_raw.train_batch(&[3, 0]),
+        DatasTrain {
+            x: array![[3., 4., 5.], [0., 1., 2.]],
+            y: array![[3.], [0.]],
+        }
+    );
+}
+
+#[test]
+fn shuffle_datas() {
+    let x = &Array2::from_shape_fn((50, 1), |(i, _)| i as f64);
+    let y = &x.clone();
+
+    let order = |seed| {
+        let mut network = NNetwork::new();
+        network
+            .import_train_datas(x, y)
+            .unwrap()
+            .add_layer(1, Activation::Linear)
+            .unwrap()
+            .init()
+            .unwrap();
+        let mut datas = network.datas.clone();
+        datas.shuffle(&mut StdRng::seed_from_u64(seed));
+        datas.order
+    };
+
+    let shuffled = order(7);
+    assert_eq!(shuffled, order(7));
+    assert_ne!(shuffled, (0..50).collect::<Vec<usize>>());
+
+    let mut sorted = shuffled.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, (0..50).collect::<Vec<usize>>());
+}
+
+#[test]
+fn import_datas_errors() {
     let x = &array![[0., 1., 2.], [1., 2., 3.], [2., 3., 4.], [3., 4., 5.]];
-    let y = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[0.], [1.], [2.]];
+
+    let mut network = NNetwork::new();
+    assert!(matches!(
+        network.import_datas(x, &array![[0.], [1.], [2.], [3.]], 1.5),
+        Err(SpitzError::InvalidRatio(_))
+    ));
+    assert!(matches!(
+        network.import_train_datas(x, y),
+        Err(SpitzError::ShapeMismatch { x: 4, y: 3 })
+    ));
+    assert!(matches!(
+        network.import_test_datas(x, y),
+        Err(SpitzError::ShapeMismatch { x: 4, y: 3 })
+    ));
+}
+
+#[test]
+fn import_datas_few_rows() {
+    let mut network = NNetwork::new();
+
+    // Both sets need at least one data
+    let empty = &Array2::zeros((0, 3));
+    assert!(matches!(
+        network.import_datas(empty, &Array2::zeros((0, 1)), 0.3),
+        Err(SpitzError::MissingTrainingDatas)
+    ));
+    assert!(matches!(
+        network.import_datas(&array![[0., 1., 2.]], &array![[0.]], 0.3),
+        Err(SpitzError::MissingTrainingDatas)
+    ));
+
+    network
+        .import_datas(&array![[0.], [1.]], &array![[0.], [1.]], 0.3)
+        .unwrap();
+    assert_eq!(network.datas_raw.train_x, array![[0.]]);
+    assert_eq!(network.datas_raw.test_x, array![[1.]]);
 
+    // Every row is used exactly once
+    let x = &array![[0.], [1.], [2.]];
+    network.import_datas(x, x, 0.5).unwrap();
+    assert_eq!(network.datas_raw.train_x, array![[0.]]);
+    assert_eq!(network.datas_raw.test_x, array![[1.], [2.]]);
+}
+
+#[test]
+fn init_incompatible_datas() {
+    let x = &array![[0., 1., 2.], [1., 2., 3.]];
+    let y = &array![[0.], [1.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .input_layer(2)
+        .add_layer(1, Activation::Linear)
+        .unwrap();
+
+    let error = network.init().err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "training inputs have 3 columns, but the input layer has 2 neurons"
+    );
+}
+
+#[test]
+fn infer_layers_sizes() {
+    let x = &array![[0., 1., 2.], [1., 2., 3.]];
+    let y = &array![[0., 1.], [1., 0.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .output_layer(Activation::Softmax)
+        .add_layer(4, Activation::Relu)
+        .unwrap()
+        .init()
+        .unwrap();
+
+    let layers = network.get_architecture().layers;
+    assert_eq!(layers.len(), 2);
+    assert_eq!((layers[0].input_size(), layers[0].output_size()), (3, 4));
+    assert_eq!((layers[1].input_size(), layers[1].output_size()), (4, 2));
+    assert_eq!(layers[1].activation(), Some(&Activation::Softmax));
+    assert_eq!(network.get_weights()[0].shape(), &[3, 4]);
+
+    // Initializing again keeps a single output layer
+    network.init().unwrap();
+    assert_eq!(network.get_architecture().layers.len(), 2);
+}
+
+#[test]
+fn output_layer_mismatch() {
+    let x = &array![[0., 1., 2.], [1., 2., 3.]];
+    let y = &array![[0., 1.], [1., 0.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .add_layer(3, Activation::Softmax)
+        .unwrap();
+
+    match network.init() {
+        Err(SpitzError::IncompatibleDataWidth {
+            datas: "training outputs",
+            expected: 3,
+            found: 2,
+        }) => (),
+        other => panic!("unexpected result : {:?}", other.err()),
+    }
+}
+
+#[test]
+fn drop_every_batch() {
+    let x = &array![[0.], [1.], [2.]];
+    let y = &array![[0.], [1.], [2.]];
+
+    // Every batch would be dropped, and no epoch could be trained
     let mut network = NNetwork::new();
-    network.import_train_datas(x, y).set_batches(1).init();
-
-    let mut wanted_datas_train: Vec<DatasTrain> = Vec::new();
-    wanted_datas_train.push(DatasTrain {
-        x: array![[0., 1., 2.]],
-        y: array![[0.]],
-    });
-    wanted_datas_train.push(DatasTrain {
-        x: array![[1., 2., 3.]],
-        y: array![[1.]],
-    });
-    wanted_datas_train.push(DatasTrain {
-        x: array![[2., 3., 4.]],
-        y: array![[2.]],
-    });
-    wanted_datas_train.push(DatasTrain {
-        x: array![[3., 4., 5.]],
-        y: array![[3.]],
-    });
-
-    assert_eq!(network.datas.train, wanted_datas_train);
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(2)
+        .unwrap()
+        .set_batches(10)
+        .unwrap()
+        .set_drop_last(true)
+        .init()
+        .unwrap();
+    assert!(matches!(network.fit(), Err(SpitzError::InvalidBatchSize)));
+    assert!(network.get_history().epochs.is_empty());
+
+    // The incomplete batch is kept otherwise
+    network.set_drop_last(false).fit().unwrap();
+    assert_eq!(network.get_history().epochs.len(), 2);
 }
@@ -0,0 +1,116 @@
+use ndarray::prelude::*;
+use spitz::*;
+
+/// Asserts that every parameter of the network passes the gradient check.
+fn assert_gradients(network: &mut NNetwork, x: &Array2<f64>, y: &Array2<f64>) {
+    let checks = network.gradient_check(x, y, 1e-5).unwrap();
+    assert!(!checks.is_empty());
+    for check in checks {
+        assert!(check.relative_error < 1e-6, "{:?}", check);
+    }
+}
+
+#[test]
+fn check_activations() {
+    let x = &array![[0.5, -1., 2.], [-0.3, 0.8, -1.5], [1.2, 0.1, 0.4]];
+    let y = &array![[0.2, 0.7], [0.9, -0.4], [-0.5, 0.3]];
+
+    for activation in &[
+        Activation::Sigmoid,
+        Activation::Tanh,
+        Activation::Linear,
+        Activation::Elu(1.),
+        Activation::Selu,
+        Activation::Gelu,
+        Activation::Swish,
+        Activation::Mish,
+        Activation::Softplus,
+    ] {
+        let mut network = NNetwork::new();
+        network
+            .set_seed(0)
+            .input_layer(3)
+            .add_layer(4, activation.clone())
+            .unwrap()
+            .add_layer(2, activation.clone())
+            .unwrap()
+            .init()
+            .unwrap();
+        assert_gradients(&mut network, x, y);
+    }
+}
+
+#[test]
+fn check_losses() {
+    let x = &array![[0.5, -1.], [-0.3, 0.8], [1.2, 0.1], [0.2, 0.3]];
+    let y = &array![[1., 0., 0.], [0., 1., 0.], [0., 0., 1.], [0., 1., 0.]];
+
+    for (activation, loss) in &[
+        (Activation::Softmax, Loss::CategoricalCrossEntropy),
+        (Activation::Softmax, Loss::MeanSquaredError),
+        (Activation::Sigmoid, Loss::BinaryCrossEntropy),
+        (Activation::Linear, Loss::Huber(0.5)),
+    ] {
+        let mut network = NNetwork::new();
+        network
+            .set_seed(1)
+            .input_layer(2)
+            .add_layer(3, Activation::Tanh)
+            .unwrap()
+            .add_layer(3, activation.clone())
+            .unwrap()
+            .set_loss(loss.clone())
+            .set_regularizer(Regularizer::L2(0.1))
+            .init()
+            .unwrap();
+        assert_gradients(&mut network, x, y);
+    }
+}
+
+#[test]
+fn check_layers() {
+    let x = &array![[0.5, -1.], [-0.3, 0.8], [1.2, 0.1], [0.2, 0.3]];
+    let y = &array![[0.2], [0.9], [-0.5], [0.1]];
+
+    let mut network = NNetwork::new();
+    network
+        .set_seed(2)
+        .input_layer(2)
+        .add_layer(5, Activation::Linear)
+        .unwrap()
+        .add(PRelu::new())
+        .add_batch_norm(BatchNorm::new())
+        .unwrap()
+        .add_dropout(Dropout::Standard(0.3))
+        .unwrap()
+        .add_layer(1, Activation::Tanh)
+        .unwrap()
+        .init()
+        .unwrap();
+    let before = network.predict(x).unwrap();
+    assert_gradients(&mut network, x, y);
+
+    // The network is left unchanged
+    assert_eq!(network.predict(x).unwrap(), before);
+    assert_eq!(network.phase(), Phase::Inference);
+}
+
+#[test]
+fn gradient_check_errors() {
+    let mut network = NNetwork::new();
+    assert!(matches!(
+        network.gradient_check(&array![[1.]], &array![[1.]], 1e-5),
+        Err(SpitzError::UninitializedNetwork)
+    ));
+
+    network
+        .input_layer(2)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+    assert!(matches!(
+        network.gradient_check(&array![[1., 2.]], &array![[1., 2.]], 1e-5),
+        Err(SpitzError::IncompatibleDataWidth { .. })
+    ));
+}
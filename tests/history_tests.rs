@@ -0,0 +1,79 @@
+use ndarray::prelude::*;
+use spitz::history::Epoch;
+use spitz::*;
+use std::time::Duration;
+
+#[test]
+fn fit_records_history() {
+    let x = &array![[0.], [1.], [2.], [3.], [4.], [5.]];
+    let y = &array![[1.], [3.], [5.], [7.], [9.], [11.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_datas(x, y, 0.34)
+        .unwrap()
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(20)
+        .unwrap()
+        .set_batches(2)
+        .unwrap()
+        .set_learning_rate(0.01)
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+
+    let history = network.get_history();
+    assert_eq!(history.epochs.len(), 20);
+    assert_eq!(history.epochs[19].epoch, 19);
+    assert_eq!(history.epochs[0].learning_rate, 0.01);
+    assert!(history.test_loss().iter().all(|l| l.is_some()));
+
+    let train_loss = history.train_loss();
+    assert!(train_loss[19] < train_loss[0]);
+
+    // A new training starts a new history
+    network.set_epochs(3).unwrap().fit().unwrap();
+    assert_eq!(network.get_history().epochs.len(), 3);
+}
+
+#[test]
+fn history_export() {
+    let mut history = History::default();
+    history.epochs.push(Epoch {
+        epoch: 0,
+        train_loss: 0.5,
+        test_loss: None,
+        metrics: vec![(String::from("accuracy"), 0.75)].into_iter().collect(),
+        learning_rate: 0.1,
+        duration: Duration::from_millis(250),
+    });
+    history.epochs.push(Epoch {
+        epoch: 1,
+        train_loss: 0.25,
+        test_loss: Some(f64::NAN),
+        metrics: Default::default(),
+        learning_rate: 0.1,
+        duration: Duration::from_secs(1),
+    });
+
+    assert_eq!(
+        history.to_csv(),
+        "epoch,train_loss,test_loss,learning_rate,duration,accuracy\n\
+         0,0.5,,0.1,0.25,0.75\n\
+         1,0.25,NaN,0.1,1,\n"
+    );
+    assert_eq!(
+        history.to_json(),
+        "{\"epochs\": [\
+         {\"epoch\": 0, \"train_loss\": 0.5, \"test_loss\": null, \"learning_rate\": 0.1, \"duration\": 0.25, \"metrics\": {\"accuracy\": 0.75}}, \
+         {\"epoch\": 1, \"train_loss\": 0.25, \"test_loss\": null, \"learning_rate\": 0.1, \"duration\": 1, \"metrics\": {}}\
+         ]}"
+    );
+    assert_eq!(history.metric("accuracy"), vec![Some(0.75), None]);
+
+    let path = std::env::temp_dir().join("spitz_history.csv");
+    history.save_csv(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), history.to_csv());
+}
@@ -0,0 +1,464 @@
+use ndarray::prelude::*;
+use ndarray_rand::rand::{rngs::StdRng, RngCore, SeedableRng};
+use spitz::*;
+use std::any::Any;
+use std::env;
+use std::fs;
+
+/// Custom layer multiplying each input by a trainable factor.
+#[derive(Clone, Debug, Default)]
+struct Scale {
+    factors: Array2<f64>,
+    factors_grad: Array2<f64>,
+    inputs: Array2<f64>,
+    size: usize,
+}
+
+impl Layer for Scale {
+    fn name(&self) -> &'static str {
+        "scale"
+    }
+    fn build(&mut self, input_shape: &[usize]) -> Result<Vec<usize>, SpitzError> {
+        self.size = input_shape.iter().product();
+        Ok(vec![self.size])
+    }
+    fn input_shape(&self) -> Vec<usize> {
+        vec![self.size]
+    }
+    fn output_shape(&self) -> Vec<usize> {
+        vec![self.size]
+    }
+    fn init(
+        &mut self,
+        _initializer: Option<&Initializer>,
+        _rng: &mut dyn RngCore,
+    ) -> Result<(), SpitzError> {
+        self.factors = Array2::ones((1, self.size));
+        Ok(())
+    }
+    fn is_initialized(&self) -> bool {
+        self.factors.shape() == [1, self.size]
+    }
+    fn forward(
+        &mut self,
+        inputs: &Array2<f64>,
+        _phase: Phase,
+        _rng: &mut dyn RngCore,
+    ) -> Array2<f64> {
+        self.inputs = inputs.clone();
+        self.infer(inputs)
+    }
+    fn infer(&self, inputs: &Array2<f64>) -> Array2<f64> {
+        inputs * &self.factors
+    }
+    fn backward(&mut self, grad: &Array2<f64>) -> Array2<f64> {
+        self.factors_grad = (grad * &self.inputs).sum_axis(Axis(0)).insert_axis(Axis(0));
+        grad * &self.factors
+    }
+    fn parameters(&self) -> Vec<&Array2<f64>> {
+        vec![&self.factors]
+    }
+    fn parameters_mut(&mut self) -> Vec<&mut Array2<f64>> {
+        vec![&mut self.factors]
+    }
+    fn gradients(&self) -> Vec<&Array2<f64>> {
+        vec![&self.factors_grad]
+    }
+    fn box_clone(&self) -> Box<dyn Layer> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[test]
+fn custom_layer() {
+    let x = &array![[1., 2.], [2., 1.], [0., 1.], [3., 3.]];
+    let y = &array![[3.], [3.], [1.], [6.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .set_seed(0)
+        .add(Scale::default())
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(50)
+        .unwrap()
+        .set_batches(4)
+        .unwrap()
+        .set_learning_rate(0.01)
+        .init()
+        .unwrap();
+    let before = network.evaluate_on(x, y).unwrap().loss;
+    network.fit().unwrap();
+    assert!(network.evaluate_on(x, y).unwrap().loss < before);
+
+    // The custom layer was trained with the others
+    let layers = network.get_architecture().layers;
+    let scale = layers[0].as_any().downcast_ref::<Scale>().unwrap();
+    assert_ne!(scale.factors, Array2::ones((1, 2)));
+    assert_eq!(network.get_weights().len(), 1);
+    assert_eq!(layers[1].input_shape(), vec![2]);
+
+    // It has no weights, and cannot be saved
+    assert!(matches!(
+        network.set_layer_regularizer(0, Regularizer::L2(0.1)),
+        Err(SpitzError::LayerWithoutWeights(0))
+    ));
+    let path = env::temp_dir().join("spitz_custom_layer.sptz");
+    fs::write(&path, "previous file").unwrap();
+    assert!(matches!(
+        network.save(&path),
+        Err(SpitzError::UnsupportedLayer(_))
+    ));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "previous file");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn dense_layer() {
+    let mut network = NNetwork::new();
+    network
+        .input_layer(3)
+        .add(
+            Dense::new(2, Activation::Relu)
+                .with_initializer(Initializer::Uniform(1., 1.))
+                .with_constraint(Constraint::MaxNorm(1.)),
+        )
+        .init()
+        .unwrap();
+
+    let layers = network.get_architecture().layers;
+    assert_eq!(layers[0].name(), "dense");
+    assert_eq!(layers[0].output_shape(), vec![2]);
+    assert_eq!(layers[0].weights(), Some(&Array2::ones((3, 2))));
+    assert_eq!(layers[0].parameters().len(), 2);
+    assert_eq!(
+        layers[0].settings().unwrap().constraint,
+        Some(Constraint::MaxNorm(1.))
+    );
+}
+
+#[test]
+fn prelu_layer() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut prelu = PRelu::new();
+    prelu.build(&[3]).unwrap();
+    prelu.init(None, &mut rng).unwrap();
+    assert_eq!(prelu.alpha, array![[0.25, 0.25, 0.25]]);
+
+    let x = array![[-4., 1., -2.], [2., -1., 0.]];
+    let y = prelu.forward(&x, Phase::Training, &mut rng);
+    assert_eq!(y, array![[-1., 1., -0.5], [2., -0.25, 0.]]);
+    assert_eq!(prelu.infer(&x), y);
+
+    let grad = prelu.backward(&array![[1., 1., 2.], [1., 3., 1.]]);
+    assert_eq!(grad, array![[0.25, 1., 0.5], [1., 0.75, 1.]]);
+    assert_eq!(prelu.gradients(), vec![&array![[-4., -3., -4.]]]);
+
+    // Its slopes are trained with the weights
+    let x = &array![[-2., 1.], [-1., 2.], [1., -1.], [2., -2.]];
+    let y = &array![[-1.], [0.], [1.], [1.]];
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .set_seed(0)
+        .add_layer(3, Activation::Linear)
+        .unwrap()
+        .add(PRelu::new())
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(20)
+        .unwrap()
+        .set_learning_rate(0.01)
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+    let layers = network.get_architecture().layers;
+    assert_ne!(layers[1].parameters()[0], &Array2::from_elem((1, 3), 0.25));
+}
+
+/// Returns a built and initialized `conv` taking images of given `shape`.
+fn built(mut conv: Conv2D, shape: &[usize]) -> (Conv2D, Vec<usize>) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let output = conv.build(shape).unwrap();
+    conv.init(None, &mut rng).unwrap();
+    (conv, output)
+}
+
+#[test]
+fn conv2d_layer() {
+    let mut rng = StdRng::seed_from_u64(0);
+    // One 3x3 image, one 2x2 kernel of ones
+    let ones =
+        Conv2D::new(1, (2, 2), Activation::Linear).with_initializer(Initializer::Uniform(1., 1.));
+    let (mut conv, output) = built(ones.clone(), &[1, 3, 3]);
+    assert_eq!(output, vec![1, 2, 2]);
+    let x = array![[1., 2., 3., 4., 5., 6., 7., 8., 9.]];
+    let y = conv.forward(&x, Phase::Training, &mut rng);
+    assert_eq!(y, array![[12., 16., 24., 28.]]);
+    assert_eq!(conv.infer(&x), y);
+
+    // Each input receives the gradients of the positions it was under the kernel
+    let grad = conv.backward(&array![[1., 1., 1., 1.]]);
+    assert_eq!(grad, array![[1., 2., 1., 2., 4., 2., 1., 2., 1.]]);
+    assert_eq!(conv.gradients()[0], &array![[12.], [16.], [24.], [28.]]);
+    assert_eq!(conv.gradients()[1], &array![[4.]]);
+
+    // Stride, padding and dilation
+    let (mut conv, output) = built(
+        ones.clone().with_padding((1, 1)).with_stride((2, 2)),
+        &[1, 3, 3],
+    );
+    assert_eq!(output, vec![1, 2, 2]);
+    assert_eq!(
+        conv.forward(&x, Phase::Training, &mut rng),
+        array![[1., 5., 11., 28.]]
+    );
+    let (mut conv, output) = built(ones.with_dilation((2, 2)), &[1, 3, 3]);
+    assert_eq!(output, vec![1, 1, 1]);
+    assert_eq!(conv.forward(&x, Phase::Training, &mut rng), array![[20.]]);
+}
+
+#[test]
+fn conv2d_formats() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let conv = Conv2D::new(3, (2, 2), Activation::Linear).with_padding((1, 0));
+    let (mut first, output) = built(conv.clone(), &[2, 3, 4]);
+    assert_eq!(output, vec![3, 4, 3]);
+    let (mut last, output) = built(conv.with_format(DataFormat::ChannelsLast), &[3, 4, 2]);
+    assert_eq!(output, vec![4, 3, 3]);
+
+    // The same images, as NCHW and NHWC samples
+    let nchw = Array::from_shape_fn((2, 2, 3, 4), |(n, c, h, w)| {
+        (n * 24 + c * 12 + h * 4 + w) as f64
+    });
+    let nhwc = nchw.clone().permuted_axes([0, 2, 3, 1]);
+    let flat =
+        |a: &Array4<f64>| Array2::from_shape_vec((2, 24), a.iter().cloned().collect()).unwrap();
+    let y_first = first.forward(&flat(&nchw), Phase::Training, &mut rng);
+    let y_last = last.forward(&flat(&nhwc), Phase::Training, &mut rng);
+
+    let y_first = y_first.into_shape((2, 3, 4, 3)).unwrap();
+    let y_last = y_last
+        .into_shape((2, 4, 3, 3))
+        .unwrap()
+        .permuted_axes([0, 3, 1, 2]);
+    assert_eq!(y_first, y_last);
+}
+
+#[test]
+fn conv2d_network() {
+    // Vertical and horizontal bars on 4x4 images
+    let mut x = Array2::zeros((8, 16));
+    let mut y = Array2::zeros((8, 2));
+    for i in 0..4 {
+        for j in 0..4 {
+            x[[i, j * 4 + i]] = 1.;
+            x[[i + 4, i * 4 + j]] = 1.;
+        }
+        y[[i, 0]] = 1.;
+        y[[i + 4, 1]] = 1.;
+    }
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(&x, &y)
+        .unwrap()
+        .set_seed(0)
+        .input_shape(&[1, 4, 4])
+        .add(
+            Conv2D::new(4, (3, 3), Activation::Relu)
+                .with_padding((1, 1))
+                .with_stride((2, 2)),
+        )
+        .output_layer(Activation::Softmax)
+        .set_loss(Loss::CategoricalCrossEntropy)
+        .set_epochs(200)
+        .unwrap()
+        .set_batches(8)
+        .unwrap()
+        .set_learning_rate(0.1)
+        .init()
+        .unwrap();
+
+    let layers = network.get_architecture().layers;
+    assert_eq!(layers[0].input_shape(), vec![1, 4, 4]);
+    assert_eq!(layers[0].output_shape(), vec![4, 2, 2]);
+    assert_eq!(layers[1].input_size(), 16);
+
+    network.fit().unwrap();
+    // Checked once trained, as null biases put some weighted sums on the kink of the relu
+    for check in network.gradient_check(&x, &y, 1e-5).unwrap() {
+        assert!(check.relative_error < 1e-6, "{:?}", check);
+    }
+    assert_eq!(
+        network.predict_classes(&x).unwrap(),
+        array![0, 0, 0, 0, 1, 1, 1, 1]
+    );
+}
+
+#[test]
+fn conv2d_errors() {
+    let mut conv = Conv2D::new(1, (3, 3), Activation::Linear);
+    assert!(matches!(
+        conv.build(&[9]),
+        Err(SpitzError::IncompatibleShape {
+            layer: "conv2d",
+            ..
+        })
+    ));
+    assert!(matches!(
+        conv.build(&[1, 2, 5]),
+        Err(SpitzError::IncompatibleShape { .. })
+    ));
+    assert!(conv.clone().with_padding((1, 0)).build(&[1, 2, 5]).is_ok());
+    assert!(matches!(
+        conv.with_stride((0, 1)).build(&[1, 5, 5]),
+        Err(SpitzError::InvalidLayerSize)
+    ));
+
+    // The input shape must be set to follow the datas
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(&Array2::zeros((2, 16)), &Array2::zeros((2, 1)))
+        .unwrap()
+        .add(Conv2D::new(1, (2, 2), Activation::Linear))
+        .output_layer(Activation::Linear);
+    assert!(matches!(
+        network.init(),
+        Err(SpitzError::IncompatibleShape { .. })
+    ));
+    network.input_shape(&[1, 4, 4]);
+    assert!(network.init().is_ok());
+}
+
+#[test]
+fn pooling_layers() {
+    let mut rng = StdRng::seed_from_u64(0);
+    // Two 2x4 channels
+    let x = array![[1., 5., 2., 0., 3., 4., 8., 6., 0., -1., -2., -3., -4., -5., -6., -7.]];
+
+    let mut max = MaxPool2D::new((2, 2));
+    assert_eq!(max.build(&[2, 2, 4]).unwrap(), vec![2, 1, 2]);
+    assert_eq!(
+        max.forward(&x, Phase::Training, &mut rng),
+        array![[5., 8., 0., -2.]]
+    );
+    // The gradients only go to the maximums
+    assert_eq!(
+        max.backward(&array![[1., 2., 3., 4.]]),
+        array![[0., 1., 0., 0., 0., 0., 2., 0., 3., 0., 4., 0., 0., 0., 0., 0.]]
+    );
+
+    let mut avg = AvgPool2D::new((2, 2)).with_stride((1, 2));
+    assert_eq!(avg.build(&[2, 2, 4]).unwrap(), vec![2, 1, 2]);
+    assert_eq!(avg.infer(&x), array![[3.25, 4., -2.5, -4.5]]);
+    avg.forward(&x, Phase::Training, &mut rng);
+    assert_eq!(
+        avg.backward(&array![[4., 8., 4., 4.]]),
+        array![[1., 1., 2., 2., 1., 1., 2., 2., 1., 1., 1., 1., 1., 1., 1., 1.]]
+    );
+
+    let mut global_max = GlobalMaxPooling2D::new();
+    assert_eq!(global_max.build(&[2, 2, 4]).unwrap(), vec![2]);
+    assert_eq!(
+        global_max.forward(&x, Phase::Training, &mut rng),
+        array![[8., 0.]]
+    );
+    assert_eq!(
+        global_max.backward(&array![[1., 2.]]),
+        array![[0., 0., 0., 0., 0., 0., 1., 0., 2., 0., 0., 0., 0., 0., 0., 0.]]
+    );
+
+    let mut global_avg = GlobalAveragePooling2D::new();
+    assert_eq!(global_avg.build(&[2, 2, 4]).unwrap(), vec![2]);
+    assert_eq!(global_avg.infer(&x), array![[3.625, -3.5]]);
+
+    // Channels last
+    let mut max = MaxPool2D::new((2, 2)).with_format(DataFormat::ChannelsLast);
+    assert_eq!(max.build(&[2, 4, 2]).unwrap(), vec![1, 2, 2]);
+    let mut global_avg = GlobalAveragePooling2D::new().with_format(DataFormat::ChannelsLast);
+    assert_eq!(global_avg.build(&[2, 4, 2]).unwrap(), vec![2]);
+    let x = array![[1., 5., 2., 0., 3., 4., 8., 6., 0., -1., -2., -3., -4., -5., -6., -7.]];
+    assert_eq!(max.infer(&x), array![[2., 5., 8., 6.]]);
+    assert_eq!(global_avg.infer(&x), array![[0.25, -0.125]]);
+}
+
+#[test]
+fn pooling_network() {
+    let x = &Array2::from_shape_fn((4, 2 * 4 * 4), |(i, j)| {
+        ((i * 7 + j * 3) % 11) as f64 / 5. - 1.
+    });
+    let y = &array![[0.5, -0.2], [0.1, 0.3], [-0.4, 0.2], [0.3, 0.3]];
+
+    let mut network = NNetwork::new();
+    network
+        .set_seed(0)
+        .input_shape(&[2, 4, 4])
+        .add(Conv2D::new(3, (3, 3), Activation::Tanh).with_padding((1, 1)))
+        .add(MaxPool2D::new((2, 2)))
+        .add(Conv2D::new(2, (2, 2), Activation::Tanh).with_padding((1, 1)))
+        .add(AvgPool2D::new((2, 2)).with_stride((1, 1)))
+        .add(GlobalMaxPooling2D::new())
+        .add_layer(2, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+
+    // Each layer is sized from the output shape of the previous one
+    let shapes: Vec<_> = network
+        .get_architecture()
+        .layers
+        .iter()
+        .map(|layer| layer.output_shape())
+        .collect();
+    assert_eq!(
+        shapes,
+        vec![
+            vec![3, 4, 4],
+            vec![3, 2, 2],
+            vec![2, 3, 3],
+            vec![2, 2, 2],
+            vec![2],
+            vec![2]
+        ]
+    );
+    for check in network.gradient_check(x, y, 1e-5).unwrap() {
+        assert!(check.relative_error < 1e-6, "{:?}", check);
+    }
+}
+
+#[test]
+fn pooling_errors() {
+    assert!(matches!(
+        MaxPool2D::new((3, 3)).build(&[1, 2, 4]),
+        Err(SpitzError::IncompatibleShape {
+            layer: "max_pool2d",
+            ..
+        })
+    ));
+    assert!(matches!(
+        AvgPool2D::new((2, 2)).build(&[8]),
+        Err(SpitzError::IncompatibleShape {
+            layer: "avg_pool2d",
+            ..
+        })
+    ));
+    assert!(matches!(
+        AvgPool2D::new((2, 2)).with_stride((0, 2)).build(&[1, 4, 4]),
+        Err(SpitzError::InvalidLayerSize)
+    ));
+    assert!(matches!(
+        GlobalAveragePooling2D::new().build(&[16]),
+        Err(SpitzError::IncompatibleShape { .. })
+    ));
+}
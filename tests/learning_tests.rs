@@ -24,23 +24,103 @@ fn train_xor() {
     let mut network = NNetwork::new();
     let _pred = network
         .import_train_datas(x, y)
+        .unwrap()
         .input_layer(2)
         .add_layer(4, Activation::Relu)
+        .unwrap()
         .add_layer(5, Activation::Sigmoid)
+        .unwrap()
         .add_layer(1, Activation::Sigmoid)
+        .unwrap()
         .set_learning_rate(0.3)
         .set_epochs(10)
+        .unwrap()
         .set_batches(1)
+        .unwrap()
         .init()
+        .unwrap()
         .fit()
-        .feed_forward(x)
-        .last()
         .unwrap()
-        .clone();
+        .predict(x)
+        .unwrap();
 
     log::info!("Done");
 }
 
+// ! LINEAR REGRESSION LEARNING TEST ---------------
+#[test]
+fn train_linear_regression() {
+    setup();
+    let x = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[1.], [3.], [5.], [7.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .input_layer(1)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_loss(Loss::MeanSquaredError)
+        .set_learning_rate(0.05)
+        .set_epochs(500)
+        .unwrap()
+        .set_batches(4)
+        .unwrap()
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+
+    let pred = network.predict(x).unwrap();
+    assert!(Loss::MeanSquaredError.loss(&pred, y) < 1e-3);
+}
+
+// ! SOFTMAX CLASSIFICATION LEARNING TEST ---------------
+#[test]
+fn train_softmax_classification() {
+    setup();
+    let x = &array![
+        [1., 0.],
+        [0.9, 0.1],
+        [0., 1.],
+        [0.1, 0.9],
+        [0., 0.],
+        [0.1, 0.]
+    ];
+    let y = &array![
+        [1., 0., 0.],
+        [1., 0., 0.],
+        [0., 1., 0.],
+        [0., 1., 0.],
+        [0., 0., 1.],
+        [0., 0., 1.]
+    ];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .input_layer(2)
+        .add_layer(3, Activation::Softmax)
+        .unwrap()
+        .set_loss(Loss::CategoricalCrossEntropy)
+        .set_learning_rate(0.5)
+        .set_epochs(300)
+        .unwrap()
+        .set_batches(6)
+        .unwrap()
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+
+    let classes = network.predict_classes(x).unwrap();
+    for (&class, t) in classes.iter().zip(y.genrows()) {
+        assert_eq!(t[class], 1.);
+    }
+}
+
 pub fn show_image(imgs: &Array2<f64>, img_to_show: usize) {
     for (id, &el) in imgs.row(img_to_show).iter().enumerate() {
         if id % 28 == 0 {
@@ -74,18 +154,24 @@ fn train_mnist() {
     let mut network = NNetwork::new();
     let pred = network
         .import_train_datas(train_images, train_labels)
+        .unwrap()
         .import_test_datas(test_images, test_labels)
+        .unwrap()
         .input_layer(784)
-        .add_layer(10, Activation::Sigmoid)
+        .add_layer(10, Activation::Softmax)
+        .unwrap()
+        .set_loss(Loss::CategoricalCrossEntropy)
         .set_learning_rate(0.03)
         .set_epochs(5)
+        .unwrap()
         .set_batches(10)
+        .unwrap()
         .init()
+        .unwrap()
         .fit()
-        .feed_forward(test_images)
-        .last()
         .unwrap()
-        .clone();
+        .predict(test_images)
+        .unwrap();
 
     log::debug!("PRED = {:8.4}", pred);
 
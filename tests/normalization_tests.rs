@@ -0,0 +1,155 @@
+use ndarray::prelude::*;
+use ndarray_rand::rand::{rngs::StdRng, SeedableRng};
+use spitz::*;
+use std::{env, fs};
+
+/// Returns a built and initialized `batch_norm` for samples of `size` values.
+fn built(mut batch_norm: BatchNorm, size: usize) -> BatchNorm {
+    batch_norm.build(&[size]).unwrap();
+    batch_norm
+        .init(None, &mut StdRng::seed_from_u64(0))
+        .unwrap();
+    batch_norm
+}
+
+#[test]
+fn batch_norm() {
+    let x = array![[1., 10.], [2., 20.], [3., 30.], [6., 40.]];
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut batch_norm = built(BatchNorm::new().with_momentum(0.5).with_epsilon(0.), 2);
+    assert_eq!(batch_norm.output_shape(), vec![2]);
+
+    // Each column is normalized over the batch
+    let output = batch_norm.forward(&x, Phase::Training, &mut rng);
+    let mean = output.mean_axis(Axis(0)).unwrap();
+    let var = output.mapv(|v| v * v).mean_axis(Axis(0)).unwrap();
+    assert!(mean.iter().all(|m| m.abs() < 1e-12));
+    assert!(var.iter().all(|v| (v - 1.).abs() < 1e-12));
+
+    // The running statistics move towards the batch ones
+    assert_eq!(batch_norm.running_mean, array![[1.5, 12.5]]);
+    assert_eq!(batch_norm.running_var, array![[2.25, 63.]]);
+
+    // Inference uses the running statistics, without changing them
+    let output = batch_norm.forward(&x, Phase::Inference, &mut rng);
+    assert_eq!(output, batch_norm.infer(&x));
+    assert_eq!(batch_norm.running_mean, array![[1.5, 12.5]]);
+}
+
+#[test]
+fn batch_norm_backward() {
+    let x = array![[0.5, -1.], [2., 0.], [-1., 3.]];
+    // The loss is the sum of the outputs weighted by `weights`
+    let weights = array![[1., 2.], [-3., 0.5], [0.2, 1.]];
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut batch_norm = built(BatchNorm::new(), 2);
+    batch_norm.gamma = array![[1.5, -0.5]];
+    batch_norm.beta = array![[0.1, 0.2]];
+
+    let mut trained = batch_norm.clone();
+    trained.forward(&x, Phase::Training, &mut rng);
+    let x_grad = trained.backward(&weights);
+    let gradients = trained.gradients();
+    assert_eq!(gradients.len(), 2);
+    assert_eq!(
+        gradients[1],
+        &weights.sum_axis(Axis(0)).insert_axis(Axis(0))
+    );
+
+    let mut loss = |batch_norm: &BatchNorm, x: &Array2<f64>| {
+        let output = batch_norm.clone().forward(x, Phase::Training, &mut rng);
+        (output * &weights).sum()
+    };
+    let eps = 1e-6;
+    for ((i, j), grad) in x_grad.indexed_iter() {
+        let (mut plus, mut minus) = (x.clone(), x.clone());
+        plus[[i, j]] += eps;
+        minus[[i, j]] -= eps;
+        let numeric = (loss(&batch_norm, &plus) - loss(&batch_norm, &minus)) / (2. * eps);
+        assert!((grad - numeric).abs() < 1e-5);
+    }
+    for j in 0..2 {
+        let (mut plus, mut minus) = (batch_norm.clone(), batch_norm.clone());
+        plus.gamma[[0, j]] += eps;
+        minus.gamma[[0, j]] -= eps;
+        let numeric = (loss(&plus, &x) - loss(&minus, &x)) / (2. * eps);
+        assert!((gradients[0][[0, j]] - numeric).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn batch_norm_backward_before_forward() {
+    // Without a forward pass, the gradients are zeros instead of a panic
+    let mut batch_norm = built(BatchNorm::new(), 2);
+    let grad = batch_norm.backward(&array![[1., 2.], [3., 4.]]);
+    assert_eq!(grad, Array2::zeros((2, 2)));
+    assert!(batch_norm
+        .gradients()
+        .iter()
+        .all(|g| **g == Array2::zeros((1, 2))));
+}
+
+#[test]
+fn fit_with_batch_norm() {
+    let x = &array![[0., 1.], [1., 0.], [1., 1.], [0., 0.]];
+    let y = &array![[1.], [1.], [0.], [0.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .set_seed(2)
+        .add_layer(6, Activation::Relu)
+        .unwrap()
+        .add_batch_norm(BatchNorm::new().with_momentum(0.9))
+        .unwrap()
+        .add_layer(6, Activation::Relu)
+        .unwrap()
+        .add_batch_norm(BatchNorm::new())
+        .unwrap()
+        .output_layer(Activation::Linear)
+        .set_epochs(200)
+        .unwrap()
+        .set_batches(4)
+        .unwrap()
+        .set_learning_rate(0.05)
+        .init()
+        .unwrap();
+    let before = network.evaluate_on(x, y).unwrap().loss;
+    network.fit().unwrap();
+    assert!(network.evaluate_on(x, y).unwrap().loss < before);
+
+    // The scales, shifts and running statistics were trained
+    let layers = network.get_architecture().layers;
+    assert_eq!(layers.len(), 5);
+    let batch_norm = layers[1].as_any().downcast_ref::<BatchNorm>().unwrap();
+    assert_eq!(batch_norm.output_shape(), vec![6]);
+    assert_ne!(batch_norm.gamma, Array2::ones((1, 6)));
+    assert_ne!(batch_norm.running_mean, Array2::zeros((1, 6)));
+
+    // Predictions use the running statistics, and are kept when saved
+    let path = env::temp_dir().join("spitz_fit_with_batch_norm.sptz");
+    network.save(&path).unwrap();
+    let loaded = NNetwork::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let loaded_layers = loaded.get_architecture().layers;
+    let loaded_batch_norm = loaded_layers[1].as_any().downcast_ref::<BatchNorm>();
+    assert_eq!(
+        loaded_batch_norm.unwrap().running_var,
+        batch_norm.running_var
+    );
+    assert_eq!(loaded.predict(x).unwrap(), network.predict(x).unwrap());
+    assert_eq!(
+        network.predict(&x.slice(s![..1, ..]).to_owned()).unwrap(),
+        network.predict(x).unwrap().slice(s![..1, ..])
+    );
+}
+
+#[test]
+fn batch_norm_errors() {
+    let mut network = NNetwork::new();
+    assert!(matches!(
+        network.add_batch_norm(BatchNorm::new()),
+        Err(SpitzError::EmptyArchitecture)
+    ));
+}
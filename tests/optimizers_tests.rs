@@ -0,0 +1,199 @@
+extern crate simple_logger;
+use ndarray::prelude::*;
+use spitz::optimizers::*;
+use spitz::*;
+
+fn assert_close(a: &Array2<f64>, b: &Array2<f64>) {
+    assert_eq!(a.shape(), b.shape());
+    for (x, y) in a.iter().zip(b) {
+        assert!((x - y).abs() < 1e-9, "{} != {}", a, b);
+    }
+}
+
+#[test]
+fn sgd_step() {
+    let grad = array![[1., -2.]];
+
+    let mut p = array![[1., 1.]];
+    let mut sgd = Sgd::new();
+    sgd.step();
+    sgd.update(0, &mut p, &grad, 0.1);
+    assert_close(&p, &array![[0.9, 1.2]]);
+
+    // Momentum accumulates velocity across steps
+    let mut p = array![[1., 1.]];
+    let mut sgd = Sgd::with_momentum(0.5);
+    sgd.update(0, &mut p, &grad, 0.1);
+    sgd.update(0, &mut p, &grad, 0.1);
+    assert_close(&p, &array![[0.75, 1.5]]);
+
+    // Nesterov looks ahead along the velocity
+    let mut p = array![[1., 1.]];
+    let mut sgd = Sgd::with_nesterov(0.5);
+    sgd.update(0, &mut p, &grad, 0.1);
+    assert_close(&p, &array![[0.85, 1.3]]);
+}
+
+#[test]
+fn adaptive_steps() {
+    let grad = array![[1., -2.]];
+
+    let mut p = array![[1., 1.]];
+    let mut adagrad = Adagrad::new(0.);
+    adagrad.update(0, &mut p, &grad, 0.1);
+    assert_close(&p, &array![[0.9, 1.1]]);
+
+    let mut p = array![[1., 1.]];
+    let mut rmsprop = RmsProp::new(0.75, 0.);
+    rmsprop.update(0, &mut p, &grad, 0.1);
+    assert_close(&p, &array![[0.8, 1.2]]);
+
+    // The first Adam step moves every parameter by `lr`
+    let mut p = array![[1., 1.]];
+    let mut adam = Adam::new(0.9, 0.999, 0.);
+    adam.step();
+    adam.update(0, &mut p, &grad, 0.1);
+    assert_close(&p, &array![[0.9, 1.1]]);
+
+    let mut p = array![[1., 1.]];
+    let mut adamw = AdamW::new(0.9, 0.999, 0., 0.5);
+    adamw.step();
+    adamw.update_weights(0, &mut p, &grad, 0.1);
+    assert_close(&p, &array![[0.85, 1.05]]);
+
+    // Biases are not decayed
+    let mut p = array![[1., 1.]];
+    adamw.update(1, &mut p, &grad, 0.1);
+    assert_close(&p, &array![[0.9, 1.1]]);
+}
+
+/// Trains a single linear neuron on null inputs : its weights get no gradient, only its biases learn.
+fn fit_null_inputs<O: Optimizer + 'static>(optimizer: O) -> NNetwork {
+    let x = &Array2::zeros((4, 1));
+    let y = &Array2::ones((4, 1));
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .input_layer(1)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_optimizer(optimizer)
+        .set_seed(0)
+        .set_learning_rate(0.1)
+        .set_epochs(10)
+        .unwrap()
+        .set_batches(4)
+        .unwrap()
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+    network
+}
+
+#[test]
+fn adamw_decays_weights_only() {
+    let adam = fit_null_inputs(Adam::default());
+    let adamw = fit_null_inputs(AdamW::new(0.9, 0.999, 1e-8, 0.5));
+
+    assert_close(&adamw.get_biases()[0], &adam.get_biases()[0]);
+    let decayed = &adam.get_weights()[0] * (1. - 0.1 * 0.5f64).powi(10);
+    assert_close(&adamw.get_weights()[0], &decayed);
+}
+
+/// Fits `y = 2x + 1` with a single linear neuron and returns the final loss.
+fn fit_linear<O: Optimizer + 'static>(optimizer: O, learning_rate: f64) -> f64 {
+    let x = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[1.], [3.], [5.], [7.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .input_layer(1)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_optimizer(optimizer)
+        .set_learning_rate(learning_rate)
+        .set_epochs(1000)
+        .unwrap()
+        .set_batches(4)
+        .unwrap()
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+
+    let pred = network.predict(x).unwrap();
+    Loss::MeanSquaredError.loss(&pred, y)
+}
+
+#[test]
+fn optimizers_learn() {
+    assert!(fit_linear(Sgd::with_momentum(0.9), 0.01) < 1e-2);
+    assert!(fit_linear(Sgd::with_nesterov(0.9), 0.01) < 1e-2);
+    assert!(fit_linear(RmsProp::default(), 0.01) < 1e-2);
+    assert!(fit_linear(Adagrad::default(), 0.5) < 1e-2);
+    assert!(fit_linear(Adam::default(), 0.05) < 1e-2);
+    assert!(fit_linear(AdamW::new(0.9, 0.999, 1e-8, 1e-4), 0.05) < 1e-2);
+}
+
+/// Trains a network with every kind of parameter for a single step with `optimizer`,
+/// and returns it along with its parameters before the step.
+fn step_layers<O: Optimizer + 'static>(optimizer: O) -> (NNetwork, NNetwork) {
+    let x = &Array2::from_shape_fn((4, 16), |(i, j)| ((i * 16 + j) % 7) as f64 / 7.);
+    let y = &array![[0.], [1.], [1.], [0.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .input_shape(&[1, 4, 4])
+        .add(Conv2D::new(2, (3, 3), Activation::Linear))
+        .add_batch_norm(BatchNorm::new())
+        .unwrap()
+        .add(PRelu::new())
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_optimizer(optimizer)
+        .set_seed(0)
+        .set_learning_rate(0.1)
+        .set_epochs(1)
+        .unwrap()
+        .set_batches(4)
+        .unwrap()
+        .init()
+        .unwrap();
+    let before = network.clone();
+    network.fit().unwrap();
+    (before, network)
+}
+
+#[test]
+fn adamw_decays_weights_of_every_layer() {
+    let (before, adam) = step_layers(Adam::default());
+    let (_, adamw) = step_layers(AdamW::new(0.9, 0.999, 1e-8, 0.5));
+
+    // Within one step, the Adam update does not depend on the parameters themselves
+    let (before, adam, adamw) = (
+        before.get_architecture().layers,
+        adam.get_architecture().layers,
+        adamw.get_architecture().layers,
+    );
+    let mut decayed = Vec::new();
+    for layer in 0..adamw.len() {
+        let initial = before[layer].parameters();
+        let adam = adam[layer].parameters();
+        for (index, adamw) in adamw[layer].parameters().into_iter().enumerate() {
+            if before[layer].weights_index() == Some(index) {
+                decayed.push(before[layer].name());
+                assert_close(adamw, &(adam[index] - &(initial[index] * 0.1 * 0.5)));
+            } else {
+                assert_close(adamw, adam[index]);
+            }
+        }
+    }
+    assert_eq!(decayed, vec!["conv2d", "dense"]);
+}
@@ -0,0 +1,203 @@
+use ndarray::prelude::*;
+use ndarray_rand::rand::{rngs::StdRng, SeedableRng};
+use spitz::*;
+use std::{env, fs};
+
+#[test]
+fn regularizers() {
+    let w = &array![[1., -2.], [0., 0.5]];
+
+    assert!((Regularizer::L1(0.1).penalty(w) - 0.35).abs() < 1e-12);
+    assert!((Regularizer::L2(0.1).penalty(w) - 0.525).abs() < 1e-12);
+    assert!((Regularizer::ElasticNet(0.1, 0.1).penalty(w) - 0.875).abs() < 1e-12);
+
+    assert_eq!(
+        Regularizer::L1(0.1).derivative(w),
+        array![[0.1, -0.1], [0., 0.1]]
+    );
+    assert_eq!(
+        Regularizer::L2(0.1).derivative(w),
+        array![[0.2, -0.4], [0., 0.1]]
+    );
+}
+
+#[test]
+fn constraints() {
+    let mut w = array![[3., 0.1], [4., 0.]];
+    Constraint::MaxNorm(1.).apply(&mut w);
+    assert_eq!(w, array![[0.6, 0.1], [0.8, 0.]]);
+
+    Constraint::UnitNorm.apply(&mut w);
+    assert!((w[[0, 1]] - 1.).abs() < 1e-12);
+    assert_eq!(w.column(0), array![0.6, 0.8]);
+}
+
+/// Returns the L2 norm of the weights of a network trained with given settings.
+fn trained_norm(regularizer: Option<Regularizer>, constraint: Option<Constraint>) -> f64 {
+    let x = &array![[0., 1.], [1., 0.], [2., 1.], [3., 2.]];
+    let y = &array![[2.], [3.], [6.], [9.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .set_seed(1)
+        .add_layer(4, Activation::Relu)
+        .unwrap()
+        .output_layer(Activation::Linear)
+        .set_epochs(100)
+        .unwrap()
+        .set_batches(4)
+        .unwrap()
+        .set_learning_rate(0.01);
+    if let Some(regularizer) = regularizer {
+        network.set_regularizer(regularizer);
+    }
+    if let Some(constraint) = constraint {
+        network.set_constraint(constraint);
+    }
+    network.init().unwrap().fit().unwrap();
+
+    network
+        .get_weights()
+        .iter()
+        .map(|w| w.mapv(|v| v * v).sum())
+        .sum::<f64>()
+        .sqrt()
+}
+
+#[test]
+fn fit_regularized() {
+    let free = trained_norm(None, None);
+    assert!(trained_norm(Some(Regularizer::L2(0.5)), None) < free);
+    assert!(trained_norm(None, Some(Constraint::MaxNorm(0.5))) <= 0.5 * 5f64.sqrt() + 1e-9);
+}
+
+#[test]
+fn layer_regularization() {
+    let x = &array![[0., 1.], [1., 0.]];
+    let y = &array![[1.], [0.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .add_layer(3, Activation::Relu)
+        .unwrap()
+        .set_layer_regularizer(0, Regularizer::L1(0.01))
+        .unwrap()
+        .output_layer(Activation::Sigmoid);
+    assert!(matches!(
+        network.set_layer_constraint(1, Constraint::UnitNorm),
+        Err(SpitzError::InvalidLayerIndex {
+            index: 1,
+            layers: 1
+        })
+    ));
+
+    // The output layer exists once initialized, and keeps its settings
+    network.init().unwrap();
+    network
+        .set_layer_constraint(1, Constraint::UnitNorm)
+        .unwrap()
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+    let layers = network.get_architecture().layers;
+    let settings = |id: usize| layers[id].settings().unwrap();
+    assert_eq!(settings(0).regularizer, Some(Regularizer::L1(0.01)));
+    assert_eq!(settings(1).constraint, Some(Constraint::UnitNorm));
+    let output = &network.get_weights()[1];
+    assert!((output.mapv(|v| v * v).sum() - 1.).abs() < 1e-9);
+}
+
+#[test]
+fn dropouts() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let outputs = Array2::from_elem((100, 100), 1.);
+
+    // Identity out of training
+    let (inferred, derivative) =
+        Dropout::Standard(0.5).forward(outputs.clone(), Phase::Inference, &mut rng);
+    assert_eq!(inferred, outputs);
+    assert!(derivative.is_none());
+
+    // About half of the outputs are dropped, the others are scaled to keep the mean
+    let (trained, derivative) =
+        Dropout::Standard(0.5).forward(outputs.clone(), Phase::Training, &mut rng);
+    assert!(trained.iter().all(|&v| v == 0. || v == 2.));
+    assert!((trained.mean().unwrap() - 1.).abs() < 0.05);
+    assert_eq!(trained, derivative.unwrap());
+
+    // Alpha dropout keeps the mean and variance of normalized outputs
+    let normal =
+        Array2::from_shape_fn((200, 100), |(i, j)| if (i + j) % 2 == 0 { 1. } else { -1. });
+    let (trained, _) = Dropout::Alpha(0.2).forward(normal, Phase::Training, &mut rng);
+    let mean = trained.mean().unwrap();
+    let variance = trained.mapv(|v| (v - mean).powi(2)).mean().unwrap();
+    assert!(mean.abs() < 0.05);
+    assert!((variance - 1.).abs() < 0.05);
+}
+
+/// Returns a network with a dropout after its hidden layer, trained with given seed.
+fn dropout_network(seed: u64) -> NNetwork {
+    let x = &array![[0., 1.], [1., 0.], [1., 1.], [0., 0.]];
+    let y = &array![[1.], [1.], [0.], [0.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .set_seed(seed)
+        .add_layer(8, Activation::Relu)
+        .unwrap()
+        .add_dropout(Dropout::Standard(0.25))
+        .unwrap()
+        .output_layer(Activation::Sigmoid)
+        .set_epochs(20)
+        .unwrap()
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+    network
+}
+
+#[test]
+fn fit_with_dropout() {
+    let network = dropout_network(3);
+    assert_eq!(network.phase(), Phase::Inference);
+    let layers = network.get_architecture().layers;
+    assert_eq!(layers.len(), 3);
+    let dropout = layers[1].as_any().downcast_ref::<layers::DropoutLayer>();
+    assert_eq!(dropout.unwrap().dropout, Dropout::Standard(0.25));
+
+    // Seeded trainings are reproducible, predictions are deterministic
+    assert_eq!(network.get_weights(), dropout_network(3).get_weights());
+    let x = &array![[0., 1.], [1., 1.]];
+    assert_eq!(network.predict(x).unwrap(), network.predict(x).unwrap());
+
+    // Dropouts are kept when saved
+    let path = env::temp_dir().join("spitz_fit_with_dropout.sptz");
+    network.save(&path).unwrap();
+    let loaded = NNetwork::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let layers = loaded.get_architecture().layers;
+    let dropout = layers[1].as_any().downcast_ref::<layers::DropoutLayer>();
+    assert_eq!(dropout.unwrap().dropout, Dropout::Standard(0.25));
+}
+
+#[test]
+fn dropout_errors() {
+    let mut network = NNetwork::new();
+    assert!(matches!(
+        network.add_dropout(Dropout::Standard(0.5)),
+        Err(SpitzError::EmptyArchitecture)
+    ));
+    network.add_layer(2, Activation::Relu).unwrap();
+    assert!(matches!(
+        network.add_dropout(Dropout::Alpha(1.)),
+        Err(SpitzError::InvalidDropoutRate(_))
+    ));
+}
@@ -0,0 +1,180 @@
+use ndarray::prelude::*;
+use spitz::history::Epoch;
+use spitz::schedules::*;
+use spitz::*;
+
+/// Returns the learning rates given by `schedule` for the first `epochs` epochs, from `1.0`.
+fn rates<S: LearningRateSchedule>(schedule: &mut S, epochs: usize) -> Vec<f64> {
+    (0..epochs).map(|e| schedule.learning_rate(1., e)).collect()
+}
+
+fn assert_close(a: &[f64], b: &[f64]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert!((a - b).abs() < 1e-9, "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn decays() {
+    assert_close(&rates(&mut Constant, 2), &[1., 1.]);
+    assert_close(
+        &rates(&mut StepDecay::new(2, 0.5), 5),
+        &[1., 1., 0.5, 0.5, 0.25],
+    );
+    assert_close(&rates(&mut ExponentialDecay::new(0.5), 3), &[1., 0.5, 0.25]);
+}
+
+#[test]
+fn cosine_and_cycles() {
+    assert_close(
+        &rates(&mut CosineAnnealing::new(2, 0.), 4),
+        &[1., 0.5, 1., 0.5],
+    );
+    // Restarts after 2, then 4 epochs
+    assert_close(
+        &rates(&mut CosineAnnealing::new(2, 0.).with_period_mult(2.), 7),
+        &[1., 0.5, 1., 0.853553390593, 0.5, 0.146446609407, 1.],
+    );
+    assert_close(
+        &rates(&mut Warmup::new(3, ExponentialDecay::new(0.5)), 5),
+        &[0.25, 0.5, 0.75, 1., 0.5],
+    );
+
+    let one_cycle = rates(&mut OneCycle::new(11), 11);
+    assert!((one_cycle[0] - 1. / 25.).abs() < 1e-9);
+    assert!((one_cycle[3] - 1.).abs() < 1e-9);
+    assert!((one_cycle[10] - 1e-4).abs() < 1e-9);
+    assert!(one_cycle.windows(2).take(3).all(|w| w[0] < w[1]));
+    assert!(one_cycle.windows(2).skip(3).all(|w| w[0] > w[1]));
+}
+
+#[test]
+fn fit_with_schedule() {
+    let x = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[1.], [3.], [5.], [7.]];
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(4)
+        .unwrap()
+        .set_learning_rate(0.1)
+        .set_learning_rate_schedule(StepDecay::new(2, 0.1))
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+    let used: Vec<f64> = network
+        .get_history()
+        .epochs
+        .iter()
+        .map(|e| e.learning_rate)
+        .collect();
+    assert_close(&used, &[0.1, 0.1, 0.01, 0.01]);
+}
+
+#[test]
+fn reduce_on_plateau() {
+    let mut schedule = ReduceOnPlateau::new(0.5, 2).with_min_learning_rate(0.2);
+    let losses = [
+        Some(1.),
+        Some(0.5),
+        Some(0.6),
+        None,
+        Some(0.5),
+        Some(0.4),
+        Some(0.9),
+        Some(0.9),
+        Some(0.9),
+        Some(0.9),
+    ];
+
+    let mut used = Vec::new();
+    for (epoch, &test_loss) in losses.iter().enumerate() {
+        used.push(schedule.learning_rate(1., epoch));
+        schedule.on_epoch_end(&Epoch {
+            epoch,
+            train_loss: 0.,
+            test_loss,
+            metrics: Default::default(),
+            learning_rate: used[epoch],
+            duration: Default::default(),
+        });
+    }
+    assert_close(&used, &[1., 1., 1., 1., 1., 0.5, 0.5, 0.5, 0.25, 0.25]);
+
+    // The minimal learning rate is kept, until the schedule is reset
+    assert_close(&[schedule.learning_rate(1., 10)], &[0.2]);
+    schedule.reset();
+    assert_close(&[schedule.learning_rate(1., 0)], &[1.]);
+}
+
+#[test]
+fn reduce_on_plateau_metric() {
+    let x = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[1.], [3.], [5.], [7.]];
+
+    // The monitored metric is evaluated without `set_evaluate_each_epoch`
+    let mut network = NNetwork::new();
+    network
+        .import_datas(x, y, 0.25)
+        .unwrap()
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(3)
+        .unwrap()
+        .set_learning_rate(0.1)
+        .set_learning_rate_schedule(
+            ReduceOnPlateau::new(0.5, 1)
+                .with_monitor(Monitor::Metric(Metric::MeanAbsoluteError))
+                .with_min_delta(1e9),
+        )
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+    let history = network.get_history();
+    assert!(history
+        .epochs
+        .iter()
+        .all(|e| e.metrics.contains_key(Metric::MeanAbsoluteError.name())));
+    let used: Vec<f64> = history.epochs.iter().map(|e| e.learning_rate).collect();
+    assert_close(&used, &[0.1, 0.1, 0.05]);
+}
+
+#[test]
+fn reduce_on_plateau_without_test_datas() {
+    let x = &array![[0.], [1.], [2.], [3.]];
+    let y = &array![[1.], [3.], [5.], [7.]];
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .set_epochs(3)
+        .unwrap()
+        .init()
+        .unwrap();
+
+    // The testing loss and the metrics are monitored on testing datas, that must be imported
+    network.set_learning_rate_schedule(ReduceOnPlateau::new(0.5, 1));
+    assert!(matches!(network.fit(), Err(SpitzError::MissingTestDatas)));
+    let monitor = Monitor::Metric(Metric::MeanAbsoluteError);
+    network.set_learning_rate_schedule(Warmup::new(
+        1,
+        ReduceOnPlateau::new(0.5, 1).with_monitor(monitor),
+    ));
+    assert!(matches!(network.fit(), Err(SpitzError::MissingTestDatas)));
+    assert!(network.get_history().epochs.is_empty());
+
+    // The training loss is always recorded
+    network
+        .set_learning_rate_schedule(ReduceOnPlateau::new(0.5, 1).with_monitor(Monitor::TrainLoss));
+    network.fit().unwrap();
+    assert_eq!(network.get_history().epochs.len(), 3);
+}
@@ -0,0 +1,347 @@
+extern crate simple_logger;
+use ndarray::prelude::*;
+use spitz::*;
+use std::env;
+use std::fs;
+use std::sync::Arc;
+
+#[test]
+fn save_and_load() {
+    let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
+    let y = &array![[0.], [1.], [1.], [0.]];
+    let path = env::temp_dir().join("spitz_save_and_load.sptz");
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .input_layer(2)
+        .add_layer(4, Activation::Relu)
+        .unwrap()
+        .add_layer(1, Activation::Sigmoid)
+        .unwrap()
+        .set_loss(Loss::Huber(0.5))
+        .set_learning_rate(0.3)
+        .set_epochs(10)
+        .unwrap()
+        .set_batches(2)
+        .unwrap()
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+    network.save(&path).unwrap();
+
+    let loaded = NNetwork::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.get_weights(), network.get_weights());
+    assert_eq!(loaded.get_biases(), network.get_biases());
+    assert_eq!(loaded.loss, Loss::Huber(0.5));
+    assert_eq!(loaded.epochs, 10);
+    assert_eq!(loaded.batches, 2);
+    assert_eq!(
+        loaded.get_architecture().layers[1].activation(),
+        Some(&Activation::Sigmoid)
+    );
+    assert_eq!(loaded.predict(x).unwrap(), network.predict(x).unwrap());
+}
+
+#[test]
+fn save_and_load_activations() {
+    let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
+    let path = env::temp_dir().join("spitz_save_and_load_activations.sptz");
+
+    let mut network = NNetwork::new();
+    network
+        .input_layer(2)
+        .add_layer(4, Activation::LeakyRelu(0.1))
+        .unwrap()
+        .add_layer(3, Activation::Linear)
+        .unwrap()
+        .add(PRelu::new().with_alpha(0.5))
+        .add_layer(1, Activation::Elu(2.))
+        .unwrap()
+        .init()
+        .unwrap();
+    network.save(&path).unwrap();
+
+    let loaded = NNetwork::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let layers = loaded.get_architecture().layers;
+    assert_eq!(layers[0].activation(), Some(&Activation::LeakyRelu(0.1)));
+    assert_eq!(layers[3].activation(), Some(&Activation::Elu(2.)));
+    assert_eq!(
+        layers[2].parameters(),
+        vec![&Array2::from_elem((1, 3), 0.5)]
+    );
+    assert_eq!(loaded.predict(x).unwrap(), network.predict(x).unwrap());
+}
+
+#[test]
+fn save_and_load_conv2d() {
+    let x = &Array2::from_shape_fn((3, 32), |(i, j)| ((i * 32 + j) % 7) as f64 - 3.);
+    let path = env::temp_dir().join("spitz_save_and_load_conv2d.sptz");
+
+    let mut network = NNetwork::new();
+    network
+        .input_shape(&[4, 4, 2])
+        .add(
+            Conv2D::new(3, (2, 3), Activation::LeakyRelu(0.2))
+                .with_stride((1, 2))
+                .with_padding((1, 1))
+                .with_dilation((2, 1))
+                .with_format(DataFormat::ChannelsLast),
+        )
+        .add_layer(2, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+    network.save(&path).unwrap();
+
+    let loaded = NNetwork::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let layers = loaded.get_architecture().layers;
+    let conv = layers[0].as_any().downcast_ref::<Conv2D>().unwrap();
+    assert_eq!(
+        (conv.kernel, conv.stride, conv.padding, conv.dilation),
+        ((2, 3), (1, 2), (1, 1), (2, 1))
+    );
+    assert_eq!(conv.format, DataFormat::ChannelsLast);
+    assert_eq!(layers[0].input_shape(), vec![4, 4, 2]);
+    assert_eq!(loaded.get_weights(), network.get_weights());
+    assert_eq!(loaded.predict(x).unwrap(), network.predict(x).unwrap());
+}
+
+/// `f(x) = x³`
+#[derive(Debug)]
+struct Cube;
+
+impl ActivationFunction for Cube {
+    fn name(&self) -> &str {
+        "cube"
+    }
+    fn forward(&self, x: Array2<f64>) -> Array2<f64> {
+        x.mapv(|x| x.powi(3))
+    }
+    fn derivative(&self, x: Array2<f64>) -> Array2<f64> {
+        x.mapv(|x| 3. * x.powi(2))
+    }
+}
+
+#[test]
+fn save_and_load_custom_activation() {
+    let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
+    let y = &array![[0.], [1.], [1.], [0.]];
+    let path = env::temp_dir().join("spitz_save_and_load_custom_activation.sptz");
+
+    let mut network = NNetwork::new();
+    network
+        .import_train_datas(x, y)
+        .unwrap()
+        .set_seed(0)
+        .add_layer(3, Activation::Custom(Arc::new(Cube)))
+        .unwrap()
+        .output_layer(Activation::Linear)
+        .set_epochs(5)
+        .unwrap()
+        .set_learning_rate(0.01)
+        .init()
+        .unwrap()
+        .fit()
+        .unwrap();
+    network.save(&path).unwrap();
+
+    // The activation must be registered to be loaded
+    assert!(matches!(
+        NNetwork::load(&path),
+        Err(SpitzError::InvalidFile(_))
+    ));
+    let mut registry = ActivationRegistry::new();
+    registry.register(Arc::new(Cube));
+    let loaded = NNetwork::load_with(&path, &registry).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        loaded.get_architecture().layers[0].activation(),
+        Some(&Activation::Custom(Arc::new(Cube)))
+    );
+    assert_eq!(loaded.predict(x).unwrap(), network.predict(x).unwrap());
+}
+
+#[test]
+fn save_and_load_pooling() {
+    let x = &Array2::from_shape_fn((2, 36), |(i, j)| ((i * 36 + j) % 5) as f64);
+    let path = env::temp_dir().join("spitz_save_and_load_pooling.sptz");
+
+    let mut network = NNetwork::new();
+    network
+        .input_shape(&[6, 6, 1])
+        .add(Conv2D::new(2, (3, 3), Activation::Relu).with_format(DataFormat::ChannelsLast))
+        .add(
+            MaxPool2D::new((2, 2))
+                .with_stride((1, 1))
+                .with_format(DataFormat::ChannelsLast),
+        )
+        .add(AvgPool2D::new((1, 3)).with_format(DataFormat::ChannelsLast))
+        .add(GlobalMaxPooling2D::new().with_format(DataFormat::ChannelsLast))
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+    let mut averaged = NNetwork::new();
+    averaged
+        .input_shape(&[1, 6, 6])
+        .add(GlobalAveragePooling2D::new())
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+
+    for network in &[network, averaged] {
+        network.save(&path).unwrap();
+        let loaded = NNetwork::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let names = |n: &NNetwork| {
+            let layers = n.get_architecture().layers;
+            layers
+                .iter()
+                .map(|l| (l.name(), l.output_shape()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&loaded), names(network));
+        assert_eq!(loaded.predict(x).unwrap(), network.predict(x).unwrap());
+    }
+}
+
+#[test]
+fn load_keeps_parameters_on_init() {
+    let x = &array![[0., 0.], [0., 1.], [1., 0.], [1., 1.]];
+    let y = &array![[0.], [1.], [1.], [0.]];
+    let path = env::temp_dir().join("spitz_load_keeps_parameters_on_init.sptz");
+
+    let mut network = NNetwork::new();
+    network
+        .input_layer(2)
+        .add_layer(1, Activation::Relu)
+        .unwrap()
+        .init()
+        .unwrap();
+    network.save(&path).unwrap();
+
+    let mut loaded = NNetwork::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    loaded.import_train_datas(x, y).unwrap().init().unwrap();
+
+    assert_eq!(loaded.get_weights(), network.get_weights());
+
+    // Only the first `init` keeps them
+    loaded.init().unwrap();
+    assert_ne!(loaded.get_weights(), network.get_weights());
+}
+
+#[test]
+fn save_errors_keep_file() {
+    let path = env::temp_dir().join("spitz_save_errors_keep_file.sptz");
+
+    let mut network = NNetwork::new();
+    network
+        .input_layer(2)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+    network.save(&path).unwrap();
+    let bytes = fs::read(&path).unwrap();
+
+    // An uninitialized network is refused before the file is touched
+    let mut uninitialized = NNetwork::new();
+    uninitialized
+        .input_layer(2)
+        .add_layer(1, Activation::Linear)
+        .unwrap();
+    assert!(matches!(
+        uninitialized.save(&path),
+        Err(SpitzError::UninitializedNetwork)
+    ));
+    assert_eq!(fs::read(&path).unwrap(), bytes);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn load_corrupted_sizes() {
+    let path = env::temp_dir().join("spitz_load_corrupted_sizes.sptz");
+
+    let mut network = NNetwork::new();
+    network
+        .input_layer(2)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+    network.save(&path).unwrap();
+    let bytes = fs::read(&path).unwrap();
+    // The size of the dense layer follows its name
+    let size = bytes.windows(5).position(|w| w == b"dense").unwrap() + 5;
+
+    // Huge sizes are refused instead of being allocated
+    let mut corrupted = bytes.clone();
+    corrupted[size..size + 8].copy_from_slice(&(u32::MAX as u64).to_le_bytes());
+    fs::write(&path, corrupted).unwrap();
+    assert!(matches!(
+        NNetwork::load(&path),
+        Err(SpitzError::InvalidFile(_))
+    ));
+
+    // As well as matrices larger than the rest of the file, e.g. the `(1, 1)` biases at the end
+    let rows = bytes.len() - 3 * 8;
+    let mut corrupted = bytes.clone();
+    corrupted[rows..rows + 8].copy_from_slice(&(u32::MAX as u64).to_le_bytes());
+    fs::write(&path, corrupted).unwrap();
+    assert!(matches!(
+        NNetwork::load(&path),
+        Err(SpitzError::InvalidFile(_))
+    ));
+
+    // And truncated files
+    fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+    assert!(matches!(
+        NNetwork::load(&path),
+        Err(SpitzError::InvalidFile(_))
+    ));
+    fs::write(&path, &bytes[..6]).unwrap();
+    assert!(matches!(NNetwork::load(&path), Err(SpitzError::Io(_))));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn load_incompatible_version() {
+    let path = env::temp_dir().join("spitz_load_incompatible_version.sptz");
+
+    let mut network = NNetwork::new();
+    network
+        .input_layer(2)
+        .add_layer(1, Activation::Linear)
+        .unwrap()
+        .init()
+        .unwrap();
+    network.save(&path).unwrap();
+
+    // Overwrite the format version, just after the magic number
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[4..8].copy_from_slice(&999u32.to_le_bytes());
+    fs::write(&path, bytes).unwrap();
+
+    let error = NNetwork::load(&path).err().unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        error,
+        SpitzError::IncompatibleVersion {
+            expected: serialization::FORMAT_VERSION,
+            found: 999
+        }
+    ));
+}